@@ -1,24 +1,49 @@
 use chrono::NaiveDate;
 use ratatui::{
-    style::{Color, Style},
-    widgets::{Block, Borders, Padding},
+    style::Style,
+    widgets::{Block, Borders, ListState, Padding},
 };
 use serde::{Deserialize, Serialize};
-use std::{char, fs};
+use std::{char, collections::BTreeMap, fs};
 use tui_textarea::{CursorMove, Input, Key, TextArea};
+use unicode_width::UnicodeWidthChar;
 
+use crate::calendar::Activity;
 use crate::config::Config;
+use crate::daterange::parse_range;
+use crate::fuzzy::rank;
+use crate::index::{load_cached, save_cache, Index};
+use crate::keymap::{KeyPress, Keymap};
+use crate::link::{link_at, Link};
+use crate::metadata::MetaValue;
+use crate::mouse::ClickAreas;
+use crate::people::{completions, mention_before_cursor, Person};
+use crate::query::{parse, Query, QueryError};
+use crate::replace::{plan, Change, Pattern, ReplaceBuffer, Undo};
+use crate::search::{search_hits, SavedSearch, SearchHit};
+use crate::stats::Stats;
+use crate::theme::Theme;
+use crate::vim::Vim;
+use crate::wrap::columns;
 
 #[derive(PartialEq)]
 pub enum CurrentScreen {
     // determines whether or not we're typing into the filter
     Main(bool),
     ViewingDay,
+    Projects,
+    People,
+    SearchResults,
+    Calendar,
+    Heatmap,
+    Stats,
 }
 
 #[derive(PartialEq, Clone)]
 pub enum Info {
     About,
+    // Problems with the key bindings in the config file, shown at start up
+    KeymapErrors(Vec<String>),
 }
 
 #[derive(PartialEq, Clone)]
@@ -27,6 +52,13 @@ pub enum Popup {
     ConfDeleteDay,
     Info(Info),
     Config(bool), // bool: whether or not we're editing
+    ProjectPicker(usize),
+    Metadata(usize, bool), // selected field, whether or not we're typing a value
+    SavedSearches(usize, bool), // selected search, whether or not we're typing a name to save as
+    Replace,
+    ReplacePreview(u16),      // scroll
+    Palette(usize),           // selected command
+    ConfCreateDay(NaiveDate), // a link to a day that hasn't been written yet
 }
 
 pub enum PopupBuffer {
@@ -111,39 +143,119 @@ pub struct App<'a> {
     pub date: chrono::NaiveDate,
     pub currently_selected: usize,
     pub text_buffer: TextArea<'a>,
+    // Mode and half-typed command of the modal editor, when the config turns it on
+    pub vim: Vim,
     pub filter_buffer: TextArea<'a>,
     pub popup: Option<Popup>,
     pub popup_buffer: NewDayBuffer,
     pub config_buffer: ConfigBuffer,
     pub file_path: String,
-    pub min_index: isize, // kind of a hack. think of a better solution
-    pub max_index: isize, // kind of a hack. think of a better solution
+    pub list_state: ListState,
     pub saving: bool,
     pub loading: bool,
     pub filter: Option<String>,
+    // Last filter that parsed, so the list doesn't jump around while typing
+    pub query: Option<Query>,
+    pub filter_error: Option<QueryError>,
+    // Treat the filter as a fuzzy pattern and rank days by it instead of sorting by date
+    pub fuzzy: bool,
     pub config: Config,
+    pub theme: Theme,
+    pub keymap: Keymap,
+    // Keys of a binding that's only been partly typed, e.g. the first `g` of `g g`
+    pub pending_keys: Vec<KeyPress>,
+    pub project_selected: usize,
+    pub project_scroll: u16,
+    pub people: Vec<Person>,
+    pub person_selected: usize,
+    pub people_scroll: u16,
+    pub meta_buffer: String,
+    pub search_name: String,
+    pub palette_query: String,
+    pub replace_buffer: ReplaceBuffer,
+    pub replace_changes: Vec<Change>,
+    // Day contents from before and after the last replace, so it can be undone in one go
+    pub replace_undo: Vec<Undo>,
+    // Day the cursor is on in the calendar and heatmap
+    pub calendar_date: NaiveDate,
+    pub heatmap_activity: Activity,
+    // Worked out when the stats screen opens, as the filter can't change while it's showing
+    pub stats: Stats,
+    // Show days as rendered markdown instead of the editor
+    pub read_mode: bool,
+    pub read_scroll: u16,
+    pub click_areas: ClickAreas,
+    // Set while the day list's scrollbar is held down
+    pub dragging_scrollbar: bool,
+    pub link_history: Vec<NaiveDate>,
+    // Where the editor is scrolled to, so links can be drawn over the right cells
+    pub editor_scroll: (u16, u16),
+    pub search_results: Vec<SearchHit>,
+    pub results_state: ListState,
+    pub index: Index,
+    // Positions in `days` that pass the filter. Kept up to date by `refresh_filtered`
+    pub filtered: Vec<usize>,
 }
 
 impl<'a> App<'a> {
     pub fn new(file_path: String, config: Config) -> Self {
+        let theme = Theme::load(&config.theme);
+        // Bindings that don't work out are reported and the defaults used instead
+        let (keymap, popup) = match Keymap::load(&config.keys) {
+            Ok(keymap) => (keymap, None),
+            Err(errors) => (
+                Keymap::default(),
+                Some(Popup::Info(Info::KeymapErrors(errors))),
+            ),
+        };
         App {
             days: Days::default(),
             should_quit: false,
             current_screen: CurrentScreen::Main(false),
             date: chrono::Local::now().date_naive(),
             currently_selected: 0,
-            text_buffer: day_text_area(None),
-            filter_buffer: day_text_area(None),
-            popup: None,
+            text_buffer: day_text_area(None, &theme),
+            filter_buffer: day_text_area(None, &theme),
+            popup,
             popup_buffer: NewDayBuffer::new(),
             config_buffer: ConfigBuffer::new(config.chars_per_line),
             file_path,
-            min_index: 0,
-            max_index: -1,
+            list_state: ListState::default(),
             saving: false,
             loading: false,
             filter: None,
+            query: None,
+            filter_error: None,
+            fuzzy: false,
             config,
+            theme,
+            keymap,
+            pending_keys: Vec::new(),
+            project_selected: 0,
+            project_scroll: 0,
+            people: Vec::new(),
+            person_selected: 0,
+            people_scroll: 0,
+            meta_buffer: String::new(),
+            search_name: String::new(),
+            palette_query: String::new(),
+            vim: Vim::default(),
+            replace_buffer: ReplaceBuffer::new(),
+            replace_changes: Vec::new(),
+            replace_undo: Vec::new(),
+            calendar_date: chrono::Local::now().date_naive(),
+            heatmap_activity: Activity::Words,
+            stats: Stats::default(),
+            read_mode: false,
+            read_scroll: 0,
+            click_areas: ClickAreas::default(),
+            dragging_scrollbar: false,
+            link_history: Vec::new(),
+            editor_scroll: (0, 0),
+            search_results: Vec::new(),
+            results_state: ListState::default(),
+            index: Index::default(),
+            filtered: Vec::new(),
         }
     }
 
@@ -153,18 +265,117 @@ impl<'a> App<'a> {
         self.currently_selected = self.days.iter().position(|d| d.date == now).unwrap();
     }
 
-    fn now(&self) -> NaiveDate {
+    pub fn now(&self) -> NaiveDate {
         chrono::Local::now().date_naive()
     }
 
+    pub fn set_filter(&mut self, filter: Option<String>) {
+        self.filter_error = None;
+        let filter_query = if self.fuzzy { None } else { filter.as_deref() };
+        match filter_query.map(|f| parse(f, &self.config.fields, self.now())) {
+            Some(Ok(query)) if query.is_empty() => self.query = None,
+            Some(Ok(query)) => self.query = Some(query),
+            Some(Err(error)) => self.filter_error = Some(error),
+            None => self.query = None,
+        }
+        self.filter = filter;
+        self.refresh_filtered();
+    }
+
+    pub fn refresh_filtered(&mut self) {
+        self.filtered = match self.filter.as_deref() {
+            Some(pattern) if self.fuzzy && !pattern.trim().is_empty() => {
+                rank(&self.days, pattern, self.now())
+            }
+            _ => self
+                .days
+                .filtered_positions(self.query.as_ref(), &self.index),
+        };
+        self.currently_selected = self
+            .currently_selected
+            .min(self.filtered.len().saturating_sub(1));
+    }
+
+    fn index_cache_path(&self) -> String {
+        format!("{}.index", self.file_path)
+    }
+
+    fn load_index(&mut self) {
+        let cached = if self.config.index_cache {
+            load_cached(&self.days, &self.index_cache_path())
+        } else {
+            None
+        };
+        self.index = match cached {
+            Some(index) => index,
+            None => Index::build(&self.days),
+        };
+    }
+
+    pub fn toggle_fuzzy(&mut self) {
+        self.fuzzy = !self.fuzzy;
+        self.currently_selected = 0;
+        self.set_filter(self.filter.clone());
+    }
+
+    pub fn fuzzy_pattern(&self) -> Option<&str> {
+        self.filter
+            .as_deref()
+            .filter(|pattern| self.fuzzy && !pattern.trim().is_empty())
+    }
+
+    pub fn apply_saved_search(&mut self, index: usize) {
+        if let Some(search) = self.config.searches.get(index) {
+            let filter = search.filter.clone();
+            self.fuzzy = false;
+            self.set_filter(Some(filter));
+        }
+    }
+
+    // Saves the current filter under `name`, replacing any search already called that
+    pub fn save_current_search(&mut self, name: &str) {
+        let name = name.trim();
+        let filter = match self.filter.as_deref() {
+            Some(filter) if !name.is_empty() && !self.fuzzy && !filter.trim().is_empty() => filter,
+            _ => return,
+        };
+        let search = SavedSearch {
+            name: name.to_string(),
+            filter: filter.to_string(),
+        };
+        match self.config.searches.iter_mut().find(|s| s.name == name) {
+            Some(existing) => *existing = search,
+            None => self.config.searches.push(search),
+        }
+        confy::store("englog", None, &self.config).expect("Failed to save config");
+    }
+
+    pub fn delete_saved_search(&mut self, index: usize) {
+        if index < self.config.searches.len() {
+            self.config.searches.remove(index);
+            confy::store("englog", None, &self.config).expect("Failed to save config");
+        }
+    }
+
+    // Days the saved search would match right now. None if it no longer parses
+    pub fn saved_search_count(&self, search: &SavedSearch) -> Option<usize> {
+        let query = parse(&search.filter, &self.config.fields, self.now()).ok()?;
+        let query = Some(query).filter(|q| !q.is_empty());
+        Some(
+            self.days
+                .filtered_positions(query.as_ref(), &self.index)
+                .len(),
+        )
+    }
+
     pub fn remove_filter(&mut self) {
-        self.filter = None;
+        self.set_filter(None);
         self.current_screen = CurrentScreen::Main(false);
     }
 
     pub fn load_days(&mut self, switch_screen: bool) {
         let mut days = match fs::read(self.file_path.as_str()) {
-            Ok(serialized) => postcard::from_bytes(&serialized).unwrap(),
+            Ok(serialized) => deserialize_days(&serialized),
             Err(_) => {
                 let days = Days::default();
                 save_inner(&days, self.file_path.as_str());
@@ -174,11 +385,15 @@ impl<'a> App<'a> {
         let now = self.now();
 
         if !days.contains_day(now) {
-            days.add(Day::new(now));
+            let day = days.new_day_from_template(now);
+            days.add(day);
         }
         let currently_selected = days.iter().position(|d| d.date == now).unwrap();
         self.days = days;
         self.currently_selected = currently_selected;
+        self.load_index();
+        self.refresh_filtered();
+        self.refresh_people();
         if switch_screen {
             self.current_screen = CurrentScreen::ViewingDay;
             self.load_text();
@@ -186,22 +401,34 @@ impl<'a> App<'a> {
     }
 
     pub fn load_text(&mut self) {
+        self.read_scroll = 0;
+        self.vim = Vim::default();
+        self.editor_scroll = (0, 0);
         let filtered_days: Vec<_> = self.filtered_days().collect();
         if filtered_days.is_empty() {
-            self.text_buffer = day_text_area(None);
+            self.text_buffer = day_text_area(None, &self.theme);
         } else {
-            self.text_buffer =
-                day_text_area(Some(filtered_days[self.currently_selected].content_into()));
+            self.text_buffer = day_text_area(
+                Some(filtered_days[self.currently_selected].content_into()),
+                &self.theme,
+            );
+        }
+        if let Some(pattern) = self.query.as_ref().and_then(|q| q.highlight_pattern()) {
+            // An unusable pattern just means nothing gets highlighted
+            if self.text_buffer.set_search_pattern(pattern).is_ok() {
+                self.text_buffer.set_search_style(self.theme.matched());
+            }
         }
     }
 
     pub fn input_to_current_day(&mut self, input: Input) {
-        if let Key::Char(_) = input.key {
+        if let Key::Char(c) = input.key {
             if let Some(limit) = self.config.chars_per_line {
                 if !input.ctrl {
                     let (y, _) = self.text_buffer.cursor();
                     let current_line = &self.text_buffer.lines()[y];
-                    if current_line.len() + 1 > limit {
+                    // Measured in columns, as bytes would break non-ASCII lines early
+                    if columns(current_line) + c.width().unwrap_or(0) > limit {
                         if current_line.split_whitespace().count() > 1 {
                             self.text_buffer.move_cursor(CursorMove::WordBack);
                             self.text_buffer.delete_next_word();
@@ -217,27 +444,72 @@ impl<'a> App<'a> {
         self.text_buffer.input(input);
     }
 
+    pub fn refresh_people(&mut self) {
+        self.people = self.days.people();
+        self.person_selected = self
+            .person_selected
+            .min(self.people.len().saturating_sub(1));
+    }
+
+    pub fn mention_completions(&self) -> Vec<&str> {
+        let (row, col) = self.text_buffer.cursor();
+        match self
+            .text_buffer
+            .lines()
+            .get(row)
+            .and_then(|line| mention_before_cursor(line, col))
+        {
+            Some(partial) => completions(&self.people, partial),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn complete_mention(&mut self) {
+        let (row, col) = self.text_buffer.cursor();
+        let partial_len = match mention_before_cursor(&self.text_buffer.lines()[row], col) {
+            Some(partial) => partial.chars().count(),
+            None => return,
+        };
+        if let Some(name) = self.mention_completions().first().map(|n| n.to_string()) {
+            for _ in 0..partial_len {
+                self.text_buffer.delete_char();
+            }
+            self.text_buffer.insert_str(name);
+        }
+    }
+
     pub fn input_to_filter_buffer(&mut self, input: Input) {
         self.filter_buffer.input(input);
-        self.filter = Some(self.filter_buffer.lines().join(""));
+        self.set_filter(Some(self.filter_buffer.lines().join("")));
         let count = self.filtered_days().count();
         if self.currently_selected >= count && count > 0 {
             self.currently_selected = count - 1;
         }
     }
 
-    // This count is getting relied on quite heavily
-    // Inefficient but it seems quite fast ATM
     pub fn filtered_days(&self) -> impl Iterator<Item = &Day> {
-        self.days.iter_filtered(self.filter.as_deref())
+        self.filtered
+            .iter()
+            .map(|position| &self.days.days[*position])
     }
 
-    pub fn filtered_days_mut(&mut self) -> impl Iterator<Item = &mut Day> {
-        self.days.iter_mut_filtered(self.filter.as_deref())
+    // `filtered` isn't in date order when fuzzy ranking, so go through it rather than `days`
+    pub fn selected_day_mut(&mut self) -> Option<&mut Day> {
+        let position = *self.filtered.get(self.currently_selected)?;
+        self.days.days.get_mut(position)
+    }
+
+    pub fn add_day(&mut self, day: Day) -> usize {
+        if !self.days.contains_day(day.date) {
+            self.index.update_day(&day);
+        }
+        let position = self.days.add(day);
+        self.refresh_filtered();
+        position
     }
 
     pub fn increment_selected(&mut self) {
-        if self.currently_selected < self.filtered_days().count() - 1 {
+        if self.currently_selected + 1 < self.filtered.len() {
             self.currently_selected += 1;
         }
     }
@@ -250,65 +522,376 @@ impl<'a> App<'a> {
 
     pub fn save(&mut self) {
         save_inner(&self.days, &self.file_path);
+        if self.config.index_cache {
+            save_cache(&self.index, &self.days, &self.index_cache_path());
+        }
+    }
+
+    // Keeps the index and filter in step with a day that was just edited
+    fn reindex_selected(&mut self) {
+        if let Some(position) = self.filtered.get(self.currently_selected) {
+            self.index.update_day(&self.days.days[*position]);
+        }
+        self.refresh_filtered();
     }
 
     pub fn update_day_from_buffer(&mut self) {
-        let selected = self.currently_selected;
         let content = Vec::from(self.text_buffer.lines());
-        let mut filtered_days: Vec<_> = self.filtered_days_mut().collect();
-        if !filtered_days.is_empty() {
-            filtered_days[selected].content = content;
+        if let Some(day) = self.selected_day_mut() {
+            day.content = content;
+        }
+        self.reindex_selected();
+    }
+
+    // Scrolls the editor without moving the cursor unless it would go out of view
+    pub fn scroll_editor(&mut self, lines: i16) {
+        // Soft wrapped days aren't drawn by the text area, so the cursor moves and the view follows
+        if self.config.soft_wrap {
+            let step = if lines < 0 {
+                CursorMove::Up
+            } else {
+                CursorMove::Down
+            };
+            for _ in 0..lines.unsigned_abs() {
+                self.text_buffer.move_cursor(step);
+            }
+            return;
         }
+        self.text_buffer.scroll((lines, 0));
     }
 
     pub fn init_filter_text(&mut self) {
         self.filter_buffer = new_text_area(
             None,
             Block::default()
-                .style(Style::default().fg(Color::White))
+                .style(self.theme.text())
                 .borders(Borders::NONE)
                 .padding(Padding::horizontal(1)),
+            &self.theme,
         );
     }
 
-    pub fn remove_day(&mut self) {
-        if self.days.len() > 0 {
-            self.days.days.remove(self.currently_selected);
-            if self.currently_selected >= self.days.len() && self.currently_selected > 0 {
-                self.currently_selected -= 1;
+    pub fn assign_project_to_selected(&mut self, project: &str) {
+        let assigned = match self.selected_day_mut() {
+            Some(day) => {
+                day.assign_project(project);
+                true
+            }
+            None => false,
+        };
+        if assigned {
+            self.reindex_selected();
+            self.save();
+        }
+    }
+
+    pub fn selected_project(&self) -> Option<&String> {
+        self.config.projects.get(self.project_selected)
+    }
+
+    pub fn selected_day(&self) -> Option<&Day> {
+        self.filtered_days().nth(self.currently_selected)
+    }
+
+    pub fn set_selected_meta(&mut self, name: &str, value: Option<MetaValue>) {
+        let changed = match self.selected_day_mut() {
+            Some(day) => {
+                day.meta.retain(|key, _| !key.eq_ignore_ascii_case(name));
+                if let Some(value) = value {
+                    day.meta.insert(name.to_string(), value);
+                }
+                true
+            }
+            None => false,
+        };
+        if changed {
+            self.refresh_filtered();
+            self.save();
+        }
+    }
+
+    // Opens `date` in the editor, creating it if needed. The filter is cleared if it hides the day
+    pub fn open_date(&mut self, date: NaiveDate) {
+        if !self.days.contains_day(date) {
+            let day = self.days.new_day_from_template(date);
+            self.add_day(day);
+            self.save();
+        }
+        if !self.filtered_days().any(|d| d.date == date) {
+            self.set_filter(None);
+        }
+        let position = self.filtered_days().position(|d| d.date == date);
+        if let Some(position) = position {
+            self.currently_selected = position;
+        }
+        self.load_text();
+        self.current_screen = CurrentScreen::ViewingDay;
+    }
+
+    // Where the text area scrolled to when it was last drawn. It keeps that to itself, but
+    // moving the cursor into view from the start of the day or of its line lands on it
+    pub fn measure_editor_scroll(&mut self) {
+        let (row, col) = self.text_buffer.cursor();
+        self.text_buffer.move_cursor(CursorMove::Jump(0, 0));
+        self.text_buffer.move_cursor(CursorMove::InViewport);
+        let top = self.text_buffer.cursor().0;
+        self.text_buffer
+            .move_cursor(CursorMove::Jump(row as u16, 0));
+        self.text_buffer.move_cursor(CursorMove::InViewport);
+        let left = self.text_buffer.cursor().1;
+        self.text_buffer
+            .move_cursor(CursorMove::Jump(row as u16, col as u16));
+        self.editor_scroll = (top as u16, left as u16);
+    }
+
+    pub fn follow_link(&mut self) {
+        let (row, col) = self.text_buffer.cursor();
+        let link = match self
+            .text_buffer
+            .lines()
+            .get(row)
+            .and_then(|l| link_at(l, col))
+        {
+            Some(link) => link,
+            None => return,
+        };
+        self.update_day_from_buffer();
+        self.save();
+        // Following a link shouldn't leave empty days behind, so ask before making one
+        if !self.days.contains_day(link.date) {
+            self.popup = Some(Popup::ConfCreateDay(link.date));
+            return;
+        }
+        self.open_link(link);
+    }
+
+    pub fn open_link(&mut self, link: Link) {
+        if let Some(day) = self.selected_day() {
+            self.link_history.push(day.date);
+        }
+        self.open_date(link.date);
+        let heading_row = link
+            .heading
+            .and_then(|heading| self.selected_day().and_then(|d| d.heading_row(&heading)));
+        if let Some(row) = heading_row {
+            self.text_buffer
+                .move_cursor(CursorMove::Jump(row as u16, 0));
+        }
+    }
+
+    pub fn follow_link_back(&mut self) {
+        if let Some(date) = self.link_history.pop() {
+            self.update_day_from_buffer();
+            self.save();
+            self.open_date(date);
+        }
+    }
+
+    // Works out what the replace popup would change. False, with the reason in the buffer, if nothing
+    pub fn preview_replace(&mut self) -> bool {
+        let today = self.now();
+        self.replace_changes.clear();
+        let buffer = &mut self.replace_buffer;
+        let planned = Pattern::new(&buffer.find, buffer.regex).and_then(|pattern| {
+            let range = parse_range(&buffer.dates, today)?;
+            let positions: Vec<usize> = if buffer.filtered_only {
+                self.filtered.clone()
+            } else {
+                (0..self.days.len()).collect()
+            };
+            Ok(plan(
+                &self.days,
+                positions.into_iter(),
+                &range,
+                &pattern,
+                &buffer.with,
+            ))
+        });
+        match planned {
+            Ok(changes) if changes.is_empty() => buffer.error = Some("no matches".to_string()),
+            Ok(changes) => self.replace_changes = changes,
+            Err(error) => buffer.error = Some(error),
+        }
+        !self.replace_changes.is_empty()
+    }
+
+    pub fn apply_replace(&mut self) {
+        self.replace_undo.clear();
+        for change in std::mem::take(&mut self.replace_changes) {
+            let day = match self.days.get_mut(change.date) {
+                Some(day) => day,
+                None => continue,
+            };
+            if self
+                .replace_undo
+                .last()
+                .is_none_or(|undo| undo.date != day.date)
+            {
+                self.replace_undo.push(Undo {
+                    date: day.date,
+                    before: day.content.clone(),
+                    after: Vec::new(),
+                });
+            }
+            if let Some(line) = day.content.get_mut(change.row) {
+                *line = change.after;
+            }
+        }
+        for undo in self.replace_undo.iter_mut() {
+            if let Some(day) = self.days.get(undo.date) {
+                undo.after = day.content.clone();
+            }
+        }
+        self.reindex_dates(self.replace_undo.iter().map(|undo| undo.date).collect());
+        self.replace_buffer = ReplaceBuffer::new();
+    }
+
+    // Days edited since the replace are left as they are, as undoing would lose the edits
+    pub fn undo_replace(&mut self) {
+        let mut dates = Vec::new();
+        for undo in std::mem::take(&mut self.replace_undo) {
+            if let Some(day) = self
+                .days
+                .get_mut(undo.date)
+                .filter(|day| day.content == undo.after)
+            {
+                day.content = undo.before;
+                dates.push(undo.date);
+            }
+        }
+        self.reindex_dates(dates);
+    }
+
+    fn reindex_dates(&mut self, dates: Vec<NaiveDate>) {
+        for date in dates {
+            if let Some(day) = self.days.get(date) {
+                self.index.update_day(day);
             }
+        }
+        self.refresh_filtered();
+        self.refresh_people();
+        self.save();
+    }
+
+    // Opens the calendar on the selected day, or today if there isn't one
+    pub fn show_calendar(&mut self) {
+        self.calendar_date = self.selected_day().map_or(self.now(), |day| day.date);
+        self.current_screen = CurrentScreen::Calendar;
+    }
+
+    pub fn show_heatmap(&mut self) {
+        self.calendar_date = self.selected_day().map_or(self.now(), |day| day.date);
+        self.current_screen = CurrentScreen::Heatmap;
+    }
+
+    pub fn show_search_results(&mut self) {
+        self.search_results = match &self.query {
+            Some(query) => search_hits(self.filtered_days(), query),
+            None => Vec::new(),
+        };
+        let selected = if self.search_results.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        self.results_state = ListState::default().with_selected(selected);
+        self.current_screen = CurrentScreen::SearchResults;
+    }
+
+    pub fn open_search_result(&mut self) {
+        let hit = match self
+            .results_state
+            .selected()
+            .and_then(|i| self.search_results.get(i))
+        {
+            Some(hit) => (hit.date, hit.row, hit.ranges.first().map(|r| r.0)),
+            None => return,
+        };
+        let (date, row, start) = hit;
+        self.open_date(date);
+        let col = start
+            .and_then(|start| {
+                let line = self.text_buffer.lines().get(row)?;
+                Some(line[..start].chars().count())
+            })
+            .unwrap_or(0);
+        self.text_buffer
+            .move_cursor(CursorMove::Jump(row as u16, col as u16));
+    }
+
+    pub fn remove_day(&mut self) {
+        if let Some(position) = self.filtered.get(self.currently_selected).copied() {
+            let day = self.days.days.remove(position);
+            self.index.remove_day(day.date);
+            self.refresh_filtered();
             self.save();
         }
     }
 }
 
+// Files written before per-day metadata existed have no header and hold `LegacyDays`
+const FILE_HEADER: &[u8] = b"ENGLOG\x01";
+
+#[derive(Deserialize)]
+struct LegacyDays {
+    days: Vec<LegacyDay>,
+}
+
+#[derive(Deserialize)]
+struct LegacyDay {
+    date: NaiveDate,
+    content: Vec<String>,
+}
+
+fn deserialize_days(serialized: &[u8]) -> Days {
+    match serialized.strip_prefix(FILE_HEADER) {
+        Some(serialized) => postcard::from_bytes(serialized).unwrap(),
+        None => {
+            let legacy: LegacyDays = postcard::from_bytes(serialized).unwrap();
+            Days {
+                days: legacy
+                    .days
+                    .into_iter()
+                    .map(|d| Day {
+                        date: d.date,
+                        content: d.content,
+                        meta: BTreeMap::new(),
+                    })
+                    .collect(),
+            }
+        }
+    }
+}
+
 pub fn save_inner(days: &Days, file_path: &str) {
-    let serialized: Vec<u8> = postcard::to_allocvec(days).unwrap();
+    let mut serialized: Vec<u8> = FILE_HEADER.to_vec();
+    serialized.extend(postcard::to_allocvec(days).unwrap());
     let _ = fs::File::create(file_path);
     fs::write(file_path, serialized).expect("Failed to write to file");
 }
 
-fn new_text_area(input: Option<Vec<String>>, block: Block<'_>) -> TextArea<'_> {
+fn new_text_area<'a>(input: Option<Vec<String>>, block: Block<'a>, theme: &Theme) -> TextArea<'a> {
     let mut textarea = match input {
         Some(input) => TextArea::new(input),
         None => TextArea::default(),
     };
     textarea.set_block(block);
-    textarea.set_style(Style::default().fg(Color::White));
+    textarea.set_style(theme.text());
     textarea.set_cursor_line_style(Style::default());
     textarea.move_cursor(CursorMove::Bottom);
     textarea.move_cursor(CursorMove::End);
     textarea
 }
 
-pub fn day_text_area<'a>(input: Option<Vec<String>>) -> TextArea<'a> {
+pub fn day_text_area<'a>(input: Option<Vec<String>>, theme: &Theme) -> TextArea<'a> {
     let mut textarea = new_text_area(
         input,
         Block::default()
             .title("Note")
-            .style(Style::default().fg(Color::White))
+            .style(theme.text())
             .borders(Borders::ALL)
             .padding(Padding::horizontal(1)),
+        theme,
     );
     textarea.set_placeholder_text("Start typing..");
     textarea
@@ -326,33 +909,42 @@ impl Days {
 
     pub fn iter_mut_filtered<'a>(
         &'a mut self,
-        string: Option<&'a str>,
-    ) -> impl Iterator<Item = &mut Day> + 'a {
-        self.days.iter_mut().filter(move |d| {
-            let date_str = d.date.format("%d/%m/%Y").to_string();
-            if let Some(string) = string {
-                date_str.contains(string)
-            } else {
-                true
-            }
-        })
+        query: Option<&'a Query>,
+    ) -> impl Iterator<Item = &'a mut Day> + 'a {
+        self.days
+            .iter_mut()
+            .filter(move |d| query.is_none_or(|q| q.matches(d)))
     }
 
-    pub fn iter_filtered<'a>(&'a self, string: Option<&'a str>) -> impl Iterator<Item = &Day> + 'a {
-        self.days.iter().filter(move |d| {
-            let date_str = d.date.format("%d/%m/%Y").to_string();
-            if let Some(string) = string {
-                date_str.contains(string)
-            } else {
-                true
-            }
-        })
+    pub fn iter_filtered<'a>(
+        &'a self,
+        query: Option<&'a Query>,
+    ) -> impl Iterator<Item = &'a Day> + 'a {
+        self.days
+            .iter()
+            .filter(move |d| query.is_none_or(|q| q.matches(d)))
     }
 
-    fn len(&self) -> usize {
+    // Same days as `iter_filtered` but only runs the query on days the index can't rule out
+    pub fn filtered_positions(&self, query: Option<&Query>, index: &Index) -> Vec<usize> {
+        let candidates = query.and_then(|q| q.candidates(index));
+        self.days
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| candidates.as_ref().is_none_or(|c| c.contains(&d.date)))
+            .filter(|(_, d)| query.is_none_or(|q| q.matches_indexed(d, Some(index))))
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
         self.days.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+
     pub fn add(&mut self, day: Day) -> usize {
         match self.days.binary_search(&day) {
             Ok(pos) => pos,
@@ -363,21 +955,38 @@ impl Days {
         }
     }
 
-    fn contains_day(&self, date: NaiveDate) -> bool {
+    pub fn get(&self, date: NaiveDate) -> Option<&Day> {
+        self.days
+            .binary_search_by(|d| d.date.cmp(&date).reverse())
+            .ok()
+            .map(|i| &self.days[i])
+    }
+
+    pub fn get_mut(&mut self, date: NaiveDate) -> Option<&mut Day> {
+        self.days
+            .binary_search_by(|d| d.date.cmp(&date).reverse())
+            .ok()
+            .map(|i| &mut self.days[i])
+    }
+
+    pub fn contains_day(&self, date: NaiveDate) -> bool {
         self.days.iter().any(|d| d.date == date)
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &Day> {
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Day> {
         self.days.iter()
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Day {
     pub date: NaiveDate,
     pub content: Vec<String>,
+    pub meta: BTreeMap<String, MetaValue>,
 }
 
+impl Eq for Day {}
+
 impl PartialEq for Day {
     fn eq(&self, other: &Self) -> bool {
         self.date == other.date
@@ -401,6 +1010,7 @@ impl Day {
         Self {
             date,
             content: Vec::new(),
+            meta: BTreeMap::new(),
         }
     }
 
@@ -412,3 +1022,13 @@ impl Day {
         self.content.clone()
     }
 }
+
+#[cfg(test)]
+impl Day {
+    // A day to test with, e.g. `Day::with_lines((2024, 1, 2), &["- [ ] one"])`
+    pub(crate) fn with_lines(date: (i32, u32, u32), lines: &[&str]) -> Self {
+        let mut day = Day::new(NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap());
+        day.content = lines.iter().map(|line| line.to_string()).collect();
+        day
+    }
+}
@@ -1,29 +1,141 @@
 extern crate englog;
 
 use anyhow::Result;
-use chrono::Days;
+use chrono::{Datelike, Days};
 use englog::app::{App, Day};
 use englog::config::Config;
+use englog::index::Index;
+use englog::query::parse;
+use std::time::Instant;
 
 const SEEDED_FILE_PATH: &str = "seed.postcard";
+const DEFAULT_DAY_COUNT: u64 = 1000;
+const BENCH_RUNS: u32 = 20;
 
+const WORDS: [&str; 24] = [
+    "postgres",
+    "migration",
+    "kafka",
+    "rebalancing",
+    "deploy",
+    "review",
+    "incident",
+    "latency",
+    "cache",
+    "refactor",
+    "pairing",
+    "planning",
+    "retro",
+    "release",
+    "schema",
+    "alerting",
+    "dashboard",
+    "rollback",
+    "benchmark",
+    "queue",
+    "timeout",
+    "flaky",
+    "test",
+    "load",
+];
+const TAGS: [&str; 4] = ["#perf", "#oncall", "#infra", "#hiring"];
+const PEOPLE: [&str; 4] = ["@alice", "@bob", "@carol", "@dave"];
+const QUERIES: [&str; 5] = [
+    "postgres",
+    "\"load test\"",
+    "#perf @alice",
+    "kafka -rollback",
+    "12/20",
+];
+
+// Usage: seed [number of days] [--bench]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let day_count = args
+        .iter()
+        .find_map(|a| a.parse().ok())
+        .unwrap_or(DEFAULT_DAY_COUNT);
+    let bench = args.iter().any(|a| a == "--bench");
+
     let cfg: Config = confy::load("englog", None)?;
     let doc_dir = dirs_next::document_dir().expect("Failed to find documents directory");
     let dir_path = format!("{}/{}", doc_dir.display(), "englog");
     std::fs::create_dir_all(&dir_path)?;
     let file_path = format!("{}/{}", dir_path, SEEDED_FILE_PATH);
     let mut app = App::new(file_path.clone(), cfg.clone());
-    for day in 1000..2000 {
+    for day in 1000..1000 + day_count {
         let date = chrono::Utc::now()
             .checked_sub_days(Days::new(day))
             .unwrap()
             .date_naive();
         let mut day = Day::new(date);
-        day.content.push("A note!".to_string());
+        day.content = note(day.date.num_days_from_ce() as u64);
         app.days.add(day);
     }
     app.save();
 
+    if bench {
+        run_bench(&app, &cfg);
+    }
+
     Ok(())
 }
+
+// Deterministic filler so benchmark runs are comparable
+fn note(seed: u64) -> Vec<String> {
+    let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+    let mut next = |n: usize| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((state >> 33) as usize) % n
+    };
+    let mut lines = Vec::new();
+    for _ in 0..3 + next(6) {
+        let mut words: Vec<&str> = (0..6 + next(7)).map(|_| WORDS[next(WORDS.len())]).collect();
+        if next(4) == 0 {
+            words.push(TAGS[next(TAGS.len())]);
+        }
+        if next(4) == 0 {
+            words.push(PEOPLE[next(PEOPLE.len())]);
+        }
+        lines.push(format!("- {}", words.join(" ")));
+    }
+    lines
+}
+
+fn run_bench(app: &App, cfg: &Config) {
+    let start = Instant::now();
+    let index = Index::build(&app.days);
+    println!(
+        "Indexed {} days ({} words) in {:?}",
+        app.days.len(),
+        index.word_count(),
+        start.elapsed()
+    );
+
+    for input in QUERIES {
+        let query = parse(input, &cfg.fields, chrono::Local::now().date_naive())
+            .expect("Benchmark queries should parse");
+
+        let start = Instant::now();
+        let mut scanned = 0;
+        for _ in 0..BENCH_RUNS {
+            scanned = app.days.iter_filtered(Some(&query)).count();
+        }
+        let scan_time = start.elapsed() / BENCH_RUNS;
+
+        let start = Instant::now();
+        let mut indexed = 0;
+        for _ in 0..BENCH_RUNS {
+            indexed = app.days.filtered_positions(Some(&query), &index).len();
+        }
+        let index_time = start.elapsed() / BENCH_RUNS;
+
+        assert_eq!(scanned, indexed, "Index disagrees with a full scan");
+        println!(
+            "{:<20} {:>5} days | scan {:>10.2?} | index {:>10.2?}",
+            input, indexed, scan_time, index_time
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+use crate::app::{Day, Days};
+
+impl Day {
+    pub fn word_count(&self) -> usize {
+        self.content
+            .iter()
+            .map(|line| line.split_whitespace().count())
+            .sum()
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.content
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .count()
+    }
+}
+
+pub fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+// Same day in the month `months` away, clamped to the end of shorter months
+pub fn shift_months(date: NaiveDate, months: i32) -> NaiveDate {
+    let shifted = if months < 0 {
+        date.checked_sub_months(Months::new(months.unsigned_abs()))
+    } else {
+        date.checked_add_months(Months::new(months as u32))
+    };
+    shifted.unwrap_or(date)
+}
+
+// Weeks covering `first` to `last`, Monday first, with None for days outside them
+fn weeks_between(first: NaiveDate, last: NaiveDate) -> Vec<[Option<NaiveDate>; 7]> {
+    let mut weeks = Vec::new();
+    let mut week_start = first - Duration::days(first.weekday().num_days_from_monday() as i64);
+    while week_start <= last {
+        let mut week = [None; 7];
+        for (offset, cell) in week.iter_mut().enumerate() {
+            let day = week_start + Duration::days(offset as i64);
+            if day >= first && day <= last {
+                *cell = Some(day);
+            }
+        }
+        weeks.push(week);
+        week_start += Duration::days(7);
+    }
+    weeks
+}
+
+pub fn month_grid(date: NaiveDate) -> Vec<[Option<NaiveDate>; 7]> {
+    let first = first_of_month(date);
+    weeks_between(first, shift_months(first, 1) - Duration::days(1))
+}
+
+pub fn year_grid(date: NaiveDate) -> Vec<[Option<NaiveDate>; 7]> {
+    let first = NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date);
+    let last = NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap_or(date);
+    weeks_between(first, last)
+}
+
+// 0 for nothing written, then 1 to 4 relative to the busiest day being shown
+pub fn activity_level(count: usize, max: usize) -> u8 {
+    if count == 0 || max == 0 {
+        0
+    } else {
+        (count * 4).div_ceil(max).clamp(1, 4) as u8
+    }
+}
+
+// What the shading counts
+#[derive(Clone, Copy, PartialEq)]
+pub enum Activity {
+    Words,
+    // Non-empty lines
+    Entries,
+}
+
+impl Activity {
+    pub fn of(&self, day: &Day) -> usize {
+        match self {
+            Activity::Words => day.word_count(),
+            Activity::Entries => day.entry_count(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Activity::Words => "words",
+            Activity::Entries => "entries",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            Activity::Words => Activity::Entries,
+            Activity::Entries => Activity::Words,
+        }
+    }
+}
+
+impl Days {
+    pub fn activity_on(&self, date: NaiveDate, activity: Activity) -> usize {
+        self.get(date).map_or(0, |day| activity.of(day))
+    }
+
+    // Most activity on any one day from `start` to `end`
+    pub fn max_activity(&self, start: NaiveDate, end: NaiveDate, activity: Activity) -> usize {
+        self.iter()
+            .filter(|day| day.date >= start && day.date <= end)
+            .map(|day| activity.of(day))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    // The days of each week, 0 for outside the month or year
+    fn days_of(grid: &[[Option<NaiveDate>; 7]]) -> Vec<[u32; 7]> {
+        grid.iter()
+            .map(|week| week.map(|cell| cell.map_or(0, |date| date.day())))
+            .collect()
+    }
+
+    #[test]
+    fn months_start_on_monday_rows() {
+        // 1 September 2024 is a Sunday, so it's alone at the end of the first week
+        let september = days_of(&month_grid(date(2024, 9, 17)));
+        assert_eq!(september.len(), 6);
+        assert_eq!(september[0], [0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(september[5], [30, 0, 0, 0, 0, 0, 0]);
+        // February 2021 fits exactly
+        let february = days_of(&month_grid(date(2021, 2, 1)));
+        assert_eq!(february.len(), 4);
+        assert_eq!(february[0], [1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(february[3], [22, 23, 24, 25, 26, 27, 28]);
+    }
+
+    #[test]
+    fn leap_years_get_the_29th() {
+        let grid = days_of(&month_grid(date(2024, 2, 10)));
+        assert_eq!(grid.last(), Some(&[26, 27, 28, 29, 0, 0, 0]));
+        let grid = days_of(&month_grid(date(2023, 2, 10)));
+        assert_eq!(grid.last(), Some(&[27, 28, 0, 0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn grids_stop_at_the_year_end() {
+        let december = days_of(&month_grid(date(2024, 12, 25)));
+        assert_eq!(december[0], [0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(december.last(), Some(&[30, 31, 0, 0, 0, 0, 0]));
+        let january = days_of(&month_grid(date(2024, 1, 1)));
+        assert_eq!(january[0], [1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn years_cover_every_day_in_weekday_rows() {
+        for (year, weeks, days) in [(2024, 53, 366), (2023, 53, 365), (2018, 53, 365)] {
+            let grid = year_grid(date(year, 6, 1));
+            assert_eq!(grid.len(), weeks, "{}", year);
+            let dates: Vec<NaiveDate> = grid.iter().flatten().flatten().copied().collect();
+            assert_eq!(dates.len(), days, "{}", year);
+            assert_eq!(dates.first(), Some(&date(year, 1, 1)));
+            assert_eq!(dates.last(), Some(&date(year, 12, 31)));
+            for week in grid.iter() {
+                for (row, cell) in week.iter().enumerate() {
+                    if let Some(day) = cell {
+                        assert_eq!(day.weekday().num_days_from_monday() as usize, row);
+                    }
+                }
+            }
+        }
+        // A leap year starting on a Sunday spills into a 54th week
+        let grid = days_of(&year_grid(date(2012, 1, 1)));
+        assert_eq!(grid.len(), 54);
+        assert_eq!(grid[0], [0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(grid[53], [31, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn shifting_months_clamps_to_shorter_months() {
+        assert_eq!(shift_months(date(2024, 1, 31), 1), date(2024, 2, 29));
+        assert_eq!(shift_months(date(2024, 3, 31), -1), date(2024, 2, 29));
+        assert_eq!(shift_months(date(2024, 2, 29), 12), date(2025, 2, 28));
+        assert_eq!(shift_months(date(2024, 1, 15), -1), date(2023, 12, 15));
+    }
+
+    #[test]
+    fn activity_is_relative_to_the_busiest_day() {
+        assert_eq!(activity_level(0, 100), 0);
+        assert_eq!(activity_level(5, 0), 0);
+        assert_eq!(activity_level(1, 100), 1);
+        assert_eq!(activity_level(50, 100), 2);
+        assert_eq!(activity_level(51, 100), 3);
+        assert_eq!(activity_level(76, 100), 4);
+        assert_eq!(activity_level(100, 100), 4);
+        // More than the busiest (from outside the range shown) is still the top level
+        assert_eq!(activity_level(500, 100), 4);
+    }
+
+    #[test]
+    fn entries_are_non_empty_lines() {
+        let day = Day::with_lines((2024, 1, 1), &["one two", "", "   ", "three"]);
+        assert_eq!(Activity::Entries.of(&day), 2);
+        assert_eq!(Activity::Words.of(&day), 3);
+        assert!(Activity::Words.toggle() == Activity::Entries);
+        assert!(Activity::Entries.toggle() == Activity::Words);
+    }
+
+    #[test]
+    fn counts_words_per_day() {
+        let days = Days {
+            days: vec![
+                Day::with_lines((2024, 1, 3), &["one two", "  three  "]),
+                Day::with_lines((2024, 1, 1), &["six words written on the first"]),
+            ],
+        };
+        assert_eq!(days.activity_on(date(2024, 1, 3), Activity::Words), 3);
+        assert_eq!(days.activity_on(date(2024, 1, 2), Activity::Words), 0);
+        assert_eq!(
+            days.max_activity(date(2024, 1, 1), date(2024, 1, 3), Activity::Words),
+            6
+        );
+        assert_eq!(
+            days.max_activity(date(2024, 1, 2), date(2024, 1, 3), Activity::Words),
+            3
+        );
+        assert_eq!(
+            days.max_activity(date(2024, 2, 1), date(2024, 2, 9), Activity::Words),
+            0
+        );
+    }
+}
@@ -1,14 +1,61 @@
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::metadata::FieldDef;
+use crate::search::SavedSearch;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Config {
     pub chars_per_line: Option<usize>,
+    #[serde(default)]
+    pub projects: Vec<String>,
+    // Lines shown either side of a match in the search results
+    #[serde(default = "default_search_context")]
+    pub search_context: usize,
+    // Keep the search index on disk next to the log so big logs start faster
+    #[serde(default)]
+    pub index_cache: bool,
+    // Edit days with vim's normal, insert and visual modes
+    #[serde(default)]
+    pub vim: bool,
+    // Wrap long lines in the editor to its width. Only changes how days are shown
+    #[serde(default)]
+    pub soft_wrap: bool,
+    // "dark", "light", "high-contrast" or the name of a file in the themes directory
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    // TOML can't have plain values after tables, so anything written as a table goes last
+    #[serde(default)]
+    pub fields: Vec<FieldDef>,
+    #[serde(default)]
+    pub searches: Vec<SavedSearch>,
+    // Key bindings by context then action, e.g. `[keys.main]` with `top = ["g g", "home"]`.
+    // Left loose so a mistyped entry is reported by the keymap rather than failing the whole file
+    #[serde(default)]
+    pub keys: BTreeMap<String, toml::Value>,
+}
+
+fn default_search_context() -> usize {
+    1
+}
+
+fn default_theme() -> String {
+    "dark".to_string()
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             chars_per_line: Some(80),
+            projects: Vec::new(),
+            fields: Vec::new(),
+            search_context: default_search_context(),
+            index_cache: false,
+            vim: false,
+            soft_wrap: false,
+            searches: Vec::new(),
+            keys: BTreeMap::new(),
+            theme: default_theme(),
         }
     }
 }
@@ -0,0 +1,335 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+// Inclusive range of days. A missing end is open, for `since`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateRange {
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+impl DateRange {
+    fn new(start: NaiveDate, end: NaiveDate) -> Self {
+        Self {
+            start: Some(start),
+            end: Some(end),
+        }
+    }
+
+    pub fn all() -> Self {
+        Self {
+            start: None,
+            end: None,
+        }
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start.is_none_or(|start| date >= start) && self.end.is_none_or(|end| date <= end)
+    }
+
+    // Days in both ranges
+    pub fn intersect(&self, other: &DateRange) -> DateRange {
+        DateRange {
+            start: self.start.max(other.start),
+            end: match (self.end, other.end) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pretty = |date: NaiveDate| date.format("%-d %B %Y").to_string();
+        match (self.start, self.end) {
+            (Some(start), Some(end)) if start == end => write!(f, "{}", pretty(start)),
+            (Some(start), Some(end)) if start > end => write!(f, "No days"),
+            (Some(start), Some(end)) => write!(f, "{} to {}", pretty(start), pretty(end)),
+            (Some(start), None) => write!(f, "Since {}", pretty(start)),
+            (None, Some(end)) => write!(f, "Until {}", pretty(end)),
+            (None, None) => write!(f, "All days"),
+        }
+    }
+}
+
+fn month_start(year: i32, month: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, 1)
+}
+
+// `months` long, starting at the first of `month`
+fn months(year: i32, month: u32, months: u32) -> Option<DateRange> {
+    let start = month_start(year, month)?;
+    let end = start.checked_add_months(Months::new(months))? - Duration::days(1);
+    Some(DateRange::new(start, end))
+}
+
+fn week_of(date: NaiveDate) -> DateRange {
+    let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+    DateRange::new(start, start + Duration::days(6))
+}
+
+fn quarter_of(date: NaiveDate) -> Option<DateRange> {
+    months(date.year(), (date.month0() / 3) * 3 + 1, 3)
+}
+
+fn year(word: &str) -> Option<i32> {
+    (word.len() == 4 && word.chars().all(|c| c.is_ascii_digit()))
+        .then(|| word.parse().ok())
+        .flatten()
+}
+
+// `Q3` or `H1`, split into the letter and number
+fn part_of_year(word: &str) -> Option<(char, u32)> {
+    let mut chars = word.chars();
+    let letter = chars.next()?.to_ascii_uppercase();
+    let number = chars.as_str().parse().ok()?;
+    match (letter, number) {
+        ('Q', 1..=4) | ('H', 1..=2) => Some((letter, number)),
+        _ => None,
+    }
+}
+
+// The quarter or half `part_of_year` read
+fn part_in_year((letter, number): (char, u32), year: i32) -> Option<DateRange> {
+    match letter {
+        'Q' => months(year, (number - 1) * 3 + 1, 3),
+        _ => months(year, (number - 1) * 6 + 1, 6),
+    }
+}
+
+// `2023-09-15`, `2023-09` or `2023`, as the range the date covers
+fn loose_date(word: &str) -> Option<DateRange> {
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Some(DateRange::new(date, date));
+    }
+    if let Some((y, m)) = word.split_once('-') {
+        return months(year(y)?, m.parse().ok()?, 1);
+    }
+    months(year(word)?, 1, 12)
+}
+
+// A loose date, or a quarter or half of this year. Only for where nothing but a date can go,
+// as a bare `2023` is just as likely to be text
+pub fn period(word: &str, today: NaiveDate) -> Option<DateRange> {
+    loose_date(word).or_else(|| part_in_year(part_of_year(word)?, today.year()))
+}
+
+fn relative(which: &str, unit: &str, today: NaiveDate) -> Option<DateRange> {
+    let this = match unit {
+        "week" => week_of(today),
+        "month" => months(today.year(), today.month(), 1)?,
+        "quarter" => quarter_of(today)?,
+        "year" => months(today.year(), 1, 12)?,
+        _ => return None,
+    };
+    match which {
+        "this" => Some(this),
+        "last" => {
+            let day_before = this.start? - Duration::days(1);
+            match unit {
+                "week" => Some(week_of(day_before)),
+                "month" => months(day_before.year(), day_before.month(), 1),
+                "quarter" => quarter_of(day_before),
+                _ => months(day_before.year(), 1, 12),
+            }
+        }
+        _ => None,
+    }
+}
+
+// Reads a date phrase such as `last week`, `Q3`, `Q3 2023` or `since 2023-09` from the start
+// of `words`, returning the range and how many words it used. Anything else, including `since`
+// without a date after it, is None so it can be read as text
+pub fn parse_phrase(words: &[&str], today: NaiveDate) -> Option<(DateRange, usize)> {
+    let first = words.first()?.to_lowercase();
+    if let Some(part) = part_of_year(&first) {
+        // This year's unless a year follows
+        return match words.get(1).and_then(|word| year(word)) {
+            Some(year) => Some((part_in_year(part, year)?, 2)),
+            None => Some((part_in_year(part, today.year())?, 1)),
+        };
+    }
+    let second = words.get(1)?.to_lowercase();
+    if first == "since" {
+        let range = period(&second, today)?;
+        return Some((
+            DateRange {
+                start: range.start,
+                end: None,
+            },
+            2,
+        ));
+    }
+    let range = relative(&first, &second, today)?;
+    Some((range, 2))
+}
+
+// A whole date phrase on its own, or a single `2023-09` or `Q3` style date. Empty means every day
+pub fn parse_range(text: &str, today: NaiveDate) -> Result<DateRange, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return Ok(DateRange::all());
+    }
+    match parse_phrase(&words, today) {
+        Some((range, used)) if used == words.len() => Ok(range),
+        _ if words.len() == 1 => period(words[0], today)
+            .ok_or_else(|| format!("\"{}\" is not a date range", text.trim())),
+        _ => Err(format!("\"{}\" is not a date range", text.trim())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    // A Wednesday
+    fn today() -> NaiveDate {
+        date(2024, 3, 13)
+    }
+
+    fn range(start: NaiveDate, end: NaiveDate) -> DateRange {
+        DateRange::new(start, end)
+    }
+
+    fn phrase(text: &str) -> Option<(DateRange, usize)> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        parse_phrase(&words, today())
+    }
+
+    fn period(word: &str) -> Option<DateRange> {
+        super::period(word, today())
+    }
+
+    #[test]
+    fn relative_phrases() {
+        assert_eq!(
+            phrase("this week"),
+            Some((range(date(2024, 3, 11), date(2024, 3, 17)), 2))
+        );
+        assert_eq!(
+            phrase("Last Month"),
+            Some((range(date(2024, 2, 1), date(2024, 2, 29)), 2))
+        );
+        assert_eq!(
+            phrase("last quarter"),
+            Some((range(date(2023, 10, 1), date(2023, 12, 31)), 2))
+        );
+        assert_eq!(
+            phrase("this year notes"),
+            Some((range(date(2024, 1, 1), date(2024, 12, 31)), 2))
+        );
+        assert_eq!(phrase("next week"), None);
+        assert_eq!(phrase("this"), None);
+        assert_eq!(phrase(""), None);
+    }
+
+    #[test]
+    fn quarters_and_halves_are_this_years_without_a_year() {
+        assert_eq!(
+            phrase("Q3 2023"),
+            Some((range(date(2023, 7, 1), date(2023, 9, 30)), 2))
+        );
+        assert_eq!(
+            phrase("h2 2023"),
+            Some((range(date(2023, 7, 1), date(2023, 12, 31)), 2))
+        );
+        assert_eq!(
+            phrase("Q3 planning"),
+            Some((range(date(2024, 7, 1), date(2024, 9, 30)), 1))
+        );
+        assert_eq!(
+            phrase("H1"),
+            Some((range(date(2024, 1, 1), date(2024, 6, 30)), 1))
+        );
+        assert_eq!(
+            phrase("q3 23"),
+            Some((range(date(2024, 7, 1), date(2024, 9, 30)), 1))
+        );
+        assert_eq!(phrase("Q5 2023"), None);
+        assert_eq!(phrase("H3"), None);
+    }
+
+    #[test]
+    fn since_only_takes_a_date() {
+        assert_eq!(
+            phrase("since 2023-09"),
+            Some((
+                DateRange {
+                    start: Some(date(2023, 9, 1)),
+                    end: None
+                },
+                2
+            ))
+        );
+        assert_eq!(phrase("since Q2").unwrap().0.start, Some(date(2024, 4, 1)));
+        assert_eq!(phrase("since then"), None);
+        assert_eq!(phrase("since"), None);
+    }
+
+    #[test]
+    fn periods_are_loose_dates_or_parts_of_this_year() {
+        assert_eq!(
+            period("2023"),
+            Some(range(date(2023, 1, 1), date(2023, 12, 31)))
+        );
+        assert_eq!(
+            period("2023-02"),
+            Some(range(date(2023, 2, 1), date(2023, 2, 28)))
+        );
+        assert_eq!(
+            period("2023-02-03"),
+            Some(range(date(2023, 2, 3), date(2023, 2, 3)))
+        );
+        assert_eq!(
+            period("q1"),
+            Some(range(date(2024, 1, 1), date(2024, 3, 31)))
+        );
+        assert_eq!(
+            period("H2"),
+            Some(range(date(2024, 7, 1), date(2024, 12, 31)))
+        );
+        for word in ["", "Q0", "H3", "2023-13", "23", "planning"] {
+            assert_eq!(period(word), None, "{}", word);
+        }
+    }
+
+    #[test]
+    fn a_whole_range_on_its_own() {
+        assert_eq!(parse_range("  ", today()), Ok(DateRange::all()));
+        assert_eq!(
+            parse_range("Q3", today()),
+            Ok(range(date(2024, 7, 1), date(2024, 9, 30)))
+        );
+        assert_eq!(
+            parse_range("last week", today()),
+            Ok(range(date(2024, 3, 4), date(2024, 3, 10)))
+        );
+        assert!(parse_range("last week please", today()).is_err());
+        assert!(parse_range("since then", today()).is_err());
+        assert!(parse_range("soon", today()).is_err());
+    }
+
+    #[test]
+    fn intersects_and_shows_ranges() {
+        let first = range(date(2024, 1, 1), date(2024, 1, 31));
+        let since = DateRange {
+            start: Some(date(2024, 1, 15)),
+            end: None,
+        };
+        assert_eq!(
+            first.intersect(&since),
+            range(date(2024, 1, 15), date(2024, 1, 31))
+        );
+        assert!(first.contains(date(2024, 1, 31)));
+        assert!(!first.contains(date(2024, 2, 1)));
+        assert_eq!(since.to_string(), "Since 15 January 2024");
+        assert_eq!(
+            range(date(2024, 2, 1), date(2024, 1, 1)).to_string(),
+            "No days"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::NaiveDate;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+
+use crate::app::{Day, Days};
+
+// Up to this much is added for a day written today, fading over the following months
+const RECENCY_BONUS: f64 = 40.0;
+const RECENCY_HALF_LIFE_DAYS: f64 = 60.0;
+
+fn words(pattern: &str) -> Vec<&str> {
+    pattern.split_whitespace().collect()
+}
+
+// Half remembered phrases rarely match word for word, so only half the words have to match
+fn required_words(words: &[&str]) -> usize {
+    words.len().div_ceil(2)
+}
+
+fn best_line_score(matcher: &SkimMatcherV2, day: &Day, word: &str) -> Option<i64> {
+    let date = day.date_pretty();
+    std::iter::once(date.as_str())
+        .chain(day.content.iter().map(String::as_str))
+        .filter_map(|line| matcher.fuzzy_match(line, word))
+        .max()
+}
+
+pub fn score_day(
+    matcher: &SkimMatcherV2,
+    day: &Day,
+    pattern: &str,
+    today: NaiveDate,
+) -> Option<i64> {
+    let words = words(pattern);
+    if words.is_empty() {
+        return None;
+    }
+    let scores: Vec<i64> = words
+        .iter()
+        .filter_map(|word| best_line_score(matcher, day, word))
+        .collect();
+    if scores.len() < required_words(&words) {
+        return None;
+    }
+    let age = (today - day.date).num_days().max(0) as f64;
+    let recency = RECENCY_BONUS * 0.5_f64.powf(age / RECENCY_HALF_LIFE_DAYS);
+    Some(scores.iter().sum::<i64>() + recency as i64)
+}
+
+// Positions in `days` of everything that matched, best first
+pub fn rank(days: &Days, pattern: &str, today: NaiveDate) -> Vec<usize> {
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut scored: Vec<(i64, usize)> = days
+        .iter()
+        .enumerate()
+        .filter_map(|(position, day)| {
+            score_day(&matcher, day, pattern, today).map(|score| (score, position))
+        })
+        .collect();
+    // Equal scores keep date order
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, position)| position).collect()
+}
+
+// Char indices in `line` matched by any word of the pattern
+pub fn matched_chars(line: &str, pattern: &str) -> Vec<usize> {
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut indices: Vec<usize> = words(pattern)
+        .into_iter()
+        .filter_map(|word| matcher.fuzzy_indices(line, word))
+        .flat_map(|(_, indices)| indices)
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    fn score(day: &Day, pattern: &str) -> Option<i64> {
+        score_day(
+            &SkimMatcherV2::default().ignore_case(),
+            day,
+            pattern,
+            today(),
+        )
+    }
+
+    #[test]
+    fn closer_matches_score_higher() {
+        let exact = Day::with_lines((2024, 6, 1), &["deploy pipeline"]);
+        let scattered = Day::with_lines((2024, 6, 1), &["do everything, plo yes"]);
+        assert!(score(&exact, "deploy").unwrap() > score(&scattered, "deploy").unwrap());
+        assert_eq!(score(&exact, "zzz"), None);
+        assert_eq!(score(&exact, "  "), None);
+        // The date is searched too
+        assert!(score(&exact, "01/06").is_some());
+    }
+
+    #[test]
+    fn newer_days_get_a_fading_bonus() {
+        let at = |date: (i32, u32, u32)| score(&Day::with_lines(date, &["standup"]), "standup");
+        let fresh = at((2024, 6, 1)).unwrap();
+        // One half life ago the bonus is half of what it is today
+        assert_eq!(fresh - at((2024, 4, 2)).unwrap(), 20);
+        // Old enough and it's all but gone
+        assert_eq!(fresh - at((2020, 6, 1)).unwrap(), 40);
+        // Days after today don't get more than today
+        assert_eq!(at((2024, 7, 1)).unwrap(), fresh);
+    }
+
+    #[test]
+    fn half_the_words_have_to_match() {
+        assert_eq!(required_words(&["a"]), 1);
+        assert_eq!(required_words(&["a", "b"]), 1);
+        assert_eq!(required_words(&["a", "b", "c"]), 2);
+        assert_eq!(required_words(&["a", "b", "c", "d"]), 2);
+        let day = Day::with_lines((2024, 6, 1), &["fixed the flaky build"]);
+        assert!(score(&day, "flaky build qqq").is_some());
+        assert!(score(&day, "flaky qqq xxx").is_none());
+    }
+
+    #[test]
+    fn ranks_best_first_then_by_position() {
+        let mut days = Days { days: Vec::new() };
+        days.add(Day::with_lines((2024, 5, 30), &["release notes"]));
+        days.add(Day::with_lines((2024, 5, 31), &["nothing to see"]));
+        days.add(Day::with_lines((2024, 6, 1), &["release"]));
+        days.add(Day::with_lines((2024, 5, 29), &["release notes"]));
+        // Newest first in `days`, so positions are 6/1, 5/31, 5/30, 5/29
+        assert_eq!(rank(&days, "release notes", today()), vec![2, 3, 0]);
+    }
+
+    #[test]
+    fn matched_chars_are_sorted_char_indices() {
+        // "é" and "è" are two bytes each, the indices count them once
+        assert_eq!(matched_chars("Café crème", "crème"), vec![5, 6, 7, 8, 9]);
+        assert_eq!(
+            matched_chars("Café crème", "crè caf"),
+            vec![0, 1, 2, 5, 6, 7]
+        );
+        assert_eq!(matched_chars("Café", "CAF"), vec![0, 1, 2]);
+        assert!(matched_chars("Café", "zzz").is_empty());
+    }
+}
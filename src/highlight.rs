@@ -0,0 +1,128 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::{ops::Range, sync::OnceLock};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{FontStyle, Theme, ThemeSet},
+    parsing::{SyntaxReference, SyntaxSet},
+};
+
+use crate::markdown::FENCE;
+
+// Grammars and themes are bundled into the binary by syntect, so nothing is read from disk
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn themes() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+fn theme(name: &str) -> Option<&'static Theme> {
+    themes().themes.get(name)
+}
+
+// Loading the grammars takes a moment, so start on it before the first code block is shown
+pub fn preload() {
+    std::thread::spawn(|| {
+        syntaxes();
+        themes();
+    });
+}
+
+// Fence tags people actually write that the bundled grammars don't know by name
+fn syntax_for(language: &str) -> Option<&'static SyntaxReference> {
+    let language = match language.to_lowercase().as_str() {
+        "shell" | "console" | "zsh" => "bash".to_string(),
+        "postgres" | "postgresql" | "psql" | "mysql" => "sql".to_string(),
+        other => other.to_string(),
+    };
+    syntaxes().find_syntax_by_token(&language)
+}
+
+fn to_style(style: syntect::highlighting::Style) -> Style {
+    let mut converted = Style::default().fg(Color::Rgb(
+        style.foreground.r,
+        style.foreground.g,
+        style.foreground.b,
+    ));
+    if style.font_style.contains(FontStyle::BOLD) {
+        converted = converted.add_modifier(Modifier::BOLD);
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        converted = converted.add_modifier(Modifier::ITALIC);
+    }
+    converted
+}
+
+// The language tag of a fence line, e.g. `sql` for "```sql"
+pub fn fence_language(line: &str) -> Option<&str> {
+    let tag = line.trim_start().strip_prefix(FENCE)?.trim();
+    tag.split_whitespace().next()
+}
+
+// Highlighted pieces of each line in `rows` inside a fenced block with a language the grammars
+// know. Everything else, including the fences and blocks in unknown languages, is None.
+// So is everything when `theme_name` isn't one of the bundled themes. Only the block the
+// first row is in and those after it get highlighted, so drawing a screenful of a long day
+// doesn't go through every block in it
+pub fn code_spans(
+    lines: &[String],
+    theme_name: &str,
+    rows: Range<usize>,
+) -> Vec<Option<Vec<(Style, String)>>> {
+    let end = rows.end.min(lines.len());
+    let start = rows.start.min(end);
+    let theme = match theme(theme_name) {
+        Some(theme) => theme,
+        None => return vec![None; end - start],
+    };
+    // Highlighting has to start from the fence of the block the first row is in
+    let mut open = None;
+    for (row, line) in lines[..start].iter().enumerate() {
+        if line.trim_start().starts_with(FENCE) {
+            open = match open {
+                Some(_) => None,
+                None => Some(row),
+            };
+        }
+    }
+    let from = open.unwrap_or(start);
+    let mut spans = Vec::with_capacity(end - from);
+    let mut block: Option<Option<HighlightLines>> = None;
+    for line in &lines[from..end] {
+        if line.trim_start().starts_with(FENCE) {
+            block = match block {
+                Some(_) => None,
+                None => Some(
+                    fence_language(line)
+                        .and_then(syntax_for)
+                        .map(|syntax| HighlightLines::new(syntax, theme)),
+                ),
+            };
+            spans.push(None);
+            continue;
+        }
+        let highlighter = match block.as_mut() {
+            Some(Some(highlighter)) => highlighter,
+            _ => {
+                spans.push(None);
+                continue;
+            }
+        };
+        let with_ending = format!("{}\n", line);
+        let highlighted = highlighter
+            .highlight_line(&with_ending, syntaxes())
+            .ok()
+            .map(|ranges| {
+                ranges
+                    .into_iter()
+                    .map(|(style, text)| (to_style(style), text.trim_end_matches('\n').to_string()))
+                    .filter(|(_, text)| !text.is_empty())
+                    .collect()
+            });
+        spans.push(highlighted);
+    }
+    spans.split_off(start - from)
+}
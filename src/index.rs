@@ -0,0 +1,240 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    hash::{Hash, Hasher},
+    ops::Bound,
+};
+
+use crate::app::{Day, Days};
+use crate::search::normalize;
+
+// Suffix of a normalized word -> days it appears in. Only used to narrow down which days are
+// worth checking properly, so a candidate that doesn't actually match is fine but a miss isn't
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Index {
+    suffixes: BTreeMap<String, BTreeSet<NaiveDate>>,
+    // What each day added, so a day can be taken out again when it changes
+    day_words: BTreeMap<NaiveDate, Vec<String>>,
+    // Normalized content and date of each day, so matches can be confirmed without redoing that work
+    texts: BTreeMap<NaiveDate, String>,
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+}
+
+// Every suffix of a word, so looking up words that start with something finds the words
+// that contain it anywhere
+fn suffixes(word: &str) -> impl Iterator<Item = &str> {
+    word.char_indices().map(move |(i, _)| &word[i..])
+}
+
+fn day_text(day: &Day) -> String {
+    let mut text = day.date_pretty();
+    for line in day.content.iter() {
+        text.push('\n');
+        text.push_str(&normalize(line));
+    }
+    text
+}
+
+fn day_words(day: &Day, text: &str) -> Vec<String> {
+    let mut day_words: Vec<String> = words(text).map(str::to_string).collect();
+    // The whole date so `12/2023` style terms narrow things down too
+    day_words.push(day.date_pretty());
+    day_words.sort();
+    day_words.dedup();
+    day_words
+}
+
+impl Index {
+    pub fn build(days: &Days) -> Self {
+        let mut index = Self::default();
+        for day in days.iter() {
+            index.update_day(day);
+        }
+        index
+    }
+
+    pub fn update_day(&mut self, day: &Day) {
+        self.remove_day(day.date);
+        let text = day_text(day);
+        let day_words = day_words(day, &text);
+        self.texts.insert(day.date, text);
+        for suffix in day_words.iter().flat_map(|word| suffixes(word)) {
+            self.suffixes
+                .entry(suffix.to_string())
+                .or_default()
+                .insert(day.date);
+        }
+        self.day_words.insert(day.date, day_words);
+    }
+
+    pub fn remove_day(&mut self, date: NaiveDate) {
+        self.texts.remove(&date);
+        for word in self.day_words.remove(&date).unwrap_or_default() {
+            for suffix in suffixes(&word) {
+                if let Some(dates) = self.suffixes.get_mut(suffix) {
+                    dates.remove(&date);
+                    if dates.is_empty() {
+                        self.suffixes.remove(suffix);
+                    }
+                }
+            }
+        }
+    }
+
+    // Days that could contain `term` as a substring. None when the term has no words to look up
+    pub fn candidates(&self, term: &str) -> Option<BTreeSet<NaiveDate>> {
+        let term = normalize(term);
+        let mut candidates: Option<BTreeSet<NaiveDate>> = None;
+        for word in words(&term) {
+            let mut dates = BTreeSet::new();
+            let starting_with = self
+                .suffixes
+                .range::<str, _>((Bound::Included(word), Bound::Unbounded))
+                .take_while(|(suffix, _)| suffix.starts_with(word));
+            for (_, suffix_dates) in starting_with {
+                dates.extend(suffix_dates.iter().copied());
+            }
+            candidates = Some(match candidates {
+                Some(candidates) => candidates.intersection(&dates).copied().collect(),
+                None => dates,
+            });
+        }
+        candidates
+    }
+
+    // Same as `Day::contains_text` for an indexed day, with `term` already normalized
+    pub fn contains_text(&self, date: NaiveDate, term: &str) -> Option<bool> {
+        self.texts
+            .get(&date)
+            .map(|text| text.lines().any(|line| line.contains(term)))
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.day_words
+            .values()
+            .flatten()
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+}
+
+// Bumped whenever what's in the index changes, so caches written by older versions get rebuilt
+const CACHE_VERSION: u32 = 2;
+
+// The on-disk cache is only trusted if it was built from exactly these days
+fn fingerprint(days: &Days) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    CACHE_VERSION.hash(&mut hasher);
+    for day in days.iter() {
+        day.date.hash(&mut hasher);
+        day.content.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[derive(Serialize)]
+struct IndexCacheRef<'a> {
+    fingerprint: u64,
+    index: &'a Index,
+}
+
+#[derive(Deserialize)]
+struct IndexCache {
+    fingerprint: u64,
+    index: Index,
+}
+
+pub fn load_cached(days: &Days, path: &str) -> Option<Index> {
+    let serialized = fs::read(path).ok()?;
+    let cache: IndexCache = postcard::from_bytes(&serialized).ok()?;
+    (cache.fingerprint == fingerprint(days)).then_some(cache.index)
+}
+
+pub fn save_cache(index: &Index, days: &Days, path: &str) {
+    let cache = IndexCacheRef {
+        fingerprint: fingerprint(days),
+        index,
+    };
+    if let Ok(serialized) = postcard::to_allocvec(&cache) {
+        // The cache is optional so failing to write it isn't worth stopping for
+        let _ = fs::write(path, serialized);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn dates(days: &[u32]) -> Option<BTreeSet<NaiveDate>> {
+        Some(days.iter().map(|&day| date(day)).collect())
+    }
+
+    fn days(contents: &[&[&str]]) -> Days {
+        let mut days = Days { days: Vec::new() };
+        for (i, content) in contents.iter().enumerate() {
+            days.add(Day::with_lines((2024, 1, i as u32 + 1), content));
+        }
+        days
+    }
+
+    #[test]
+    fn finds_terms_anywhere_in_a_word() {
+        let index = Index::build(&days(&[&["Perf tuning"], &["load testing"], &["Café"]]));
+        assert_eq!(index.candidates("tun"), dates(&[1]));
+        assert_eq!(index.candidates("uning"), dates(&[1]));
+        assert_eq!(index.candidates("d test"), dates(&[2]));
+        assert_eq!(index.candidates("CAFE\u{301}"), dates(&[3]));
+        assert_eq!(index.candidates("t"), dates(&[1, 2]));
+        assert_eq!(index.candidates("nowhere"), dates(&[]));
+        assert_eq!(index.candidates(" - "), None);
+        // Five words, plus the dates both whole and in pieces
+        assert_eq!(index.word_count(), 12);
+    }
+
+    #[test]
+    fn stays_in_sync_as_days_change() {
+        let mut index = Index::build(&days(&[&["alpha"], &["alphabet"]]));
+        assert_eq!(index.candidates("alpha"), dates(&[1, 2]));
+
+        index.update_day(&Day::with_lines((2024, 1, 1), &["beta"]));
+        assert_eq!(index.candidates("alpha"), dates(&[2]));
+        assert_eq!(index.candidates("bet"), dates(&[1, 2]));
+        assert_eq!(index.contains_text(date(1), "alpha"), Some(false));
+
+        index.remove_day(date(2));
+        assert_eq!(index.candidates("alpha"), dates(&[]));
+        assert_eq!(index.candidates("bet"), dates(&[1]));
+        assert_eq!(index.contains_text(date(2), "bet"), None);
+        // Nothing is left behind for removed words
+        assert!(index
+            .suffixes
+            .keys()
+            .all(|suffix| "beta".ends_with(suffix.as_str())
+                || "01/01/2024".contains(suffix.as_str())));
+    }
+
+    #[test]
+    fn rebuilds_when_the_cache_is_stale() {
+        let path = std::env::temp_dir().join(format!("englog-index-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let days = days(&[&["cached"]]);
+        save_cache(&Index::build(&days), &days, path);
+        assert!(load_cached(&days, path).is_some());
+
+        let mut changed = days;
+        changed.days[0].content = vec!["edited elsewhere".to_string()];
+        assert!(load_cached(&changed, path).is_none());
+        let _ = fs::remove_file(path);
+        assert!(load_cached(&changed, path).is_none());
+    }
+}
@@ -0,0 +1,762 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{collections::BTreeMap, fmt};
+
+use crate::app::{App, CurrentScreen, Popup};
+
+// Where a key was pressed. Every context has its own bindings, so `t` can mean
+// "today" in the calendar without getting in the way anywhere else
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Context {
+    Main,
+    // Typing into the filter
+    Filter,
+    // Editing a day. Keys that aren't bound are typed into the note
+    Day,
+    Read,
+    Projects,
+    People,
+    Calendar,
+    Heatmap,
+    SearchResults,
+    Stats,
+    // Popups that aren't asking for text
+    Popup,
+}
+
+const CONTEXTS: [(Context, &str); 11] = [
+    (Context::Main, "main"),
+    (Context::Filter, "filter"),
+    (Context::Day, "day"),
+    (Context::Read, "read"),
+    (Context::Projects, "projects"),
+    (Context::People, "people"),
+    (Context::Calendar, "calendar"),
+    (Context::Heatmap, "heatmap"),
+    (Context::SearchResults, "search-results"),
+    (Context::Stats, "stats"),
+    (Context::Popup, "popup"),
+];
+
+impl Context {
+    pub fn name(&self) -> &'static str {
+        CONTEXTS
+            .iter()
+            .find(|(context, _)| context == self)
+            .map_or("", |(_, name)| name)
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        CONTEXTS
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(context, _)| *context)
+    }
+
+    pub fn all() -> impl Iterator<Item = Context> {
+        CONTEXTS.iter().map(|(context, _)| *context)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Quit,
+    Palette,
+    Back,
+    Open,
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    PageUp,
+    PageDown,
+    ScrollUp,
+    ScrollDown,
+    Top,
+    Bottom,
+    Today,
+    Previous,
+    Next,
+    Filter,
+    ClearFilter,
+    Done,
+    ToggleFuzzy,
+    NewDay,
+    DeleteDay,
+    Info,
+    Config,
+    AssignProject,
+    Projects,
+    Metadata,
+    People,
+    MatchingLines,
+    SavedSearches,
+    Replace,
+    UndoReplace,
+    Calendar,
+    Heatmap,
+    Stats,
+    ReadMode,
+    FollowLink,
+    LinkBack,
+    Complete,
+    Reflow,
+    ReflowDay,
+    SoftWrap,
+    ToggleActivity,
+    Select,
+    Edit,
+    Delete,
+    Add,
+    Confirm,
+    Deny,
+}
+
+// Name in the config file and what the footer calls it
+const ACTIONS: [(Action, &str, &str); 50] = [
+    (Action::Quit, "quit", "quit"),
+    (Action::Palette, "palette", "commands"),
+    (Action::Back, "back", "back"),
+    (Action::Open, "open", "open day"),
+    (Action::MoveUp, "move-up", "up"),
+    (Action::MoveDown, "move-down", "down"),
+    (Action::MoveLeft, "move-left", "left"),
+    (Action::MoveRight, "move-right", "right"),
+    (Action::PageUp, "page-up", "page up"),
+    (Action::PageDown, "page-down", "page down"),
+    (Action::ScrollUp, "scroll-up", "scroll up"),
+    (Action::ScrollDown, "scroll-down", "scroll down"),
+    (Action::Top, "top", "top"),
+    (Action::Bottom, "bottom", "bottom"),
+    (Action::Today, "today", "today"),
+    (Action::Previous, "previous", "previous"),
+    (Action::Next, "next", "next"),
+    (Action::Filter, "filter", "filter"),
+    (Action::ClearFilter, "clear-filter", "clear filter"),
+    (Action::Done, "done", "done"),
+    (Action::ToggleFuzzy, "toggle-fuzzy", "toggle fuzzy"),
+    (Action::NewDay, "new-day", "new day / go to date"),
+    (Action::DeleteDay, "delete-day", "delete day"),
+    (Action::Info, "info", "info"),
+    (Action::Config, "config", "config"),
+    (Action::AssignProject, "assign-project", "assign project"),
+    (Action::Projects, "projects", "projects"),
+    (Action::Metadata, "metadata", "metadata"),
+    (Action::People, "people", "people"),
+    (Action::MatchingLines, "matching-lines", "matching lines"),
+    (Action::SavedSearches, "saved-searches", "saved searches"),
+    (Action::Replace, "replace", "replace"),
+    (Action::UndoReplace, "undo-replace", "undo replace"),
+    (Action::Calendar, "calendar", "calendar"),
+    (Action::Heatmap, "heatmap", "heatmap"),
+    (Action::Stats, "stats", "stats"),
+    (Action::ReadMode, "read-mode", "read mode"),
+    (
+        Action::FollowLink,
+        "follow-link",
+        "follow [[yyyy-mm-dd]] link",
+    ),
+    (Action::LinkBack, "link-back", "jump back"),
+    (Action::Complete, "complete", "complete"),
+    (Action::Reflow, "reflow", "reflow paragraph"),
+    (Action::ReflowDay, "reflow-day", "reflow day"),
+    (Action::SoftWrap, "soft-wrap", "soft wrap"),
+    (Action::ToggleActivity, "toggle-activity", "words/entries"),
+    (Action::Select, "select", "select"),
+    (Action::Edit, "edit", "edit"),
+    (Action::Delete, "delete", "delete"),
+    (Action::Add, "add", "save current filter"),
+    (Action::Confirm, "confirm", "yes"),
+    (Action::Deny, "deny", "no"),
+];
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(action, _, _)| action == self)
+            .map_or("", |(_, name, _)| name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(action, _, _)| action == self)
+            .map_or("", |(_, _, description)| description)
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        ACTIONS
+            .iter()
+            .find(|(_, n, _)| *n == name)
+            .map(|(action, _, _)| *action)
+    }
+}
+
+// Bindings each context starts with, in the order the footer lists them.
+// A binding is a space separated sequence of keys, e.g. "g g"
+fn defaults(context: Context) -> Vec<(Action, Vec<&'static str>)> {
+    let back = (Action::Back, vec!["esc", "q"]);
+    let up = (Action::MoveUp, vec!["k", "up"]);
+    let down = (Action::MoveDown, vec!["j", "down"]);
+    let page_up = (Action::PageUp, vec!["ctrl-u"]);
+    let page_down = (Action::PageDown, vec!["ctrl-d"]);
+    let palette = (Action::Palette, vec!["ctrl-p", "f1"]);
+    match context {
+        Context::Main => vec![
+            (Action::Quit, vec!["q"]),
+            palette,
+            (Action::Open, vec!["enter", "l", "right"]),
+            up,
+            down,
+            page_up,
+            page_down,
+            (Action::Top, vec!["g g"]),
+            (Action::Bottom, vec!["b"]),
+            (Action::Today, vec!["r"]),
+            (Action::Filter, vec![":"]),
+            (Action::ClearFilter, vec!["esc"]),
+            (Action::MatchingLines, vec!["s"]),
+            (Action::NewDay, vec!["n"]),
+            (Action::DeleteDay, vec!["d"]),
+            (Action::AssignProject, vec!["p"]),
+            (Action::Projects, vec!["P"]),
+            (Action::Metadata, vec!["m"]),
+            (Action::People, vec!["@"]),
+            (Action::SavedSearches, vec!["f"]),
+            (Action::Replace, vec!["R"]),
+            (Action::UndoReplace, vec!["U"]),
+            (Action::Calendar, vec!["C"]),
+            (Action::Heatmap, vec!["Y"]),
+            (Action::Stats, vec!["S"]),
+            (Action::Config, vec!["c"]),
+            (Action::Info, vec!["i"]),
+        ],
+        Context::Filter => vec![
+            (Action::Done, vec!["enter"]),
+            (Action::ClearFilter, vec!["esc"]),
+            (Action::ToggleFuzzy, vec!["ctrl-f"]),
+        ],
+        // ctrl-p pastes in the editor
+        Context::Day => vec![
+            (Action::Back, vec!["esc"]),
+            (Action::Palette, vec!["f1"]),
+            (Action::ReadMode, vec!["ctrl-t"]),
+            (Action::FollowLink, vec!["ctrl-g"]),
+            (Action::LinkBack, vec!["ctrl-o"]),
+            (Action::Complete, vec!["tab"]),
+            (Action::Reflow, vec!["alt-q"]),
+            (Action::ReflowDay, vec!["alt-Q"]),
+            (Action::SoftWrap, vec!["alt-w"]),
+        ],
+        Context::Read => vec![
+            back,
+            palette,
+            (Action::ScrollDown, vec!["j", "down"]),
+            (Action::ScrollUp, vec!["k", "up"]),
+            page_down,
+            page_up,
+            (Action::ReadMode, vec!["ctrl-t"]),
+        ],
+        Context::Projects | Context::People => vec![
+            back,
+            palette,
+            up,
+            down,
+            (Action::ScrollDown, vec!["J"]),
+            (Action::ScrollUp, vec!["K"]),
+            page_down,
+            page_up,
+        ],
+        Context::Calendar | Context::Heatmap => {
+            let mut bindings = vec![
+                back,
+                palette,
+                (Action::MoveLeft, vec!["h", "left"]),
+                (Action::MoveRight, vec!["l", "right"]),
+                up,
+                down,
+                (Action::Previous, vec!["H"]),
+                (Action::Next, vec!["L"]),
+                (Action::Today, vec!["t"]),
+                (Action::Open, vec!["enter"]),
+            ];
+            if context == Context::Heatmap {
+                bindings.push((Action::ToggleActivity, vec!["m"]));
+            }
+            bindings
+        }
+        Context::SearchResults => vec![
+            back,
+            palette,
+            (Action::Open, vec!["enter", "l", "right"]),
+            up,
+            down,
+            page_up,
+            page_down,
+        ],
+        Context::Stats => vec![back, palette],
+        Context::Popup => vec![
+            back,
+            up,
+            down,
+            (Action::Select, vec!["enter"]),
+            (Action::Edit, vec!["e", "space"]),
+            (Action::Delete, vec!["x", "delete"]),
+            (Action::Add, vec!["a"]),
+            (Action::Confirm, vec!["y", "Y"]),
+            (Action::Deny, vec!["n"]),
+        ],
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for KeyPress {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers =
+            event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        // Shift is already part of the character, e.g. `G`
+        if let KeyCode::Char(_) | KeyCode::BackTab = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+const NAMED_KEYS: [(KeyCode, &str); 16] = [
+    (KeyCode::Enter, "enter"),
+    (KeyCode::Esc, "esc"),
+    (KeyCode::Tab, "tab"),
+    (KeyCode::BackTab, "backtab"),
+    (KeyCode::Backspace, "backspace"),
+    (KeyCode::Delete, "delete"),
+    (KeyCode::Insert, "insert"),
+    (KeyCode::Home, "home"),
+    (KeyCode::End, "end"),
+    (KeyCode::PageUp, "pageup"),
+    (KeyCode::PageDown, "pagedown"),
+    (KeyCode::Up, "up"),
+    (KeyCode::Down, "down"),
+    (KeyCode::Left, "left"),
+    (KeyCode::Right, "right"),
+    (KeyCode::Char(' '), "space"),
+];
+
+const MODIFIERS: [(KeyModifiers, &str); 3] = [
+    (KeyModifiers::CONTROL, "ctrl-"),
+    (KeyModifiers::ALT, "alt-"),
+    (KeyModifiers::SHIFT, "shift-"),
+];
+
+impl KeyPress {
+    // `q`, `G`, `ctrl-d`, `alt-enter`, `f5`, `space` and so on
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while rest.chars().count() > 1 {
+            let prefix = MODIFIERS
+                .iter()
+                .find(|(_, prefix)| rest.to_lowercase().starts_with(prefix));
+            match prefix {
+                Some((modifier, prefix)) => {
+                    modifiers |= *modifier;
+                    rest = &rest[prefix.len()..];
+                }
+                None => break,
+            }
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            // The terminal sends `shift-a` as `A`, so that's what it has to match
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_uppercase().next().unwrap_or(c))
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let lower = rest.to_lowercase();
+                let named = NAMED_KEYS
+                    .iter()
+                    .find(|(_, name)| *name == lower)
+                    .map(|(code, _)| *code);
+                let function = lower
+                    .strip_prefix('f')
+                    .and_then(|n| n.parse::<u8>().ok())
+                    .filter(|n| (1..=12).contains(n))
+                    .map(KeyCode::F);
+                named
+                    .or(function)
+                    .ok_or_else(|| format!("unknown key \"{}\"", text))?
+            }
+        };
+        Ok(KeyPress::from(KeyEvent::new(code, modifiers)))
+    }
+}
+
+impl fmt::Display for KeyPress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, prefix) in MODIFIERS.iter() {
+            if self.modifiers.contains(*modifier) {
+                write!(f, "{}", prefix)?;
+            }
+        }
+        match NAMED_KEYS.iter().find(|(code, _)| *code == self.code) {
+            Some((_, name)) => write!(f, "{}", name),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{}", c),
+                KeyCode::F(n) => write!(f, "f{}", n),
+                other => write!(f, "{:?}", other),
+            },
+        }
+    }
+}
+
+pub type Sequence = Vec<KeyPress>;
+
+// Every action available in a context with the sequences bound to it
+type Bindings = Vec<(Action, Vec<Sequence>)>;
+
+fn parse_sequence(text: &str) -> Result<Sequence, String> {
+    let keys = text
+        .split_whitespace()
+        .map(KeyPress::parse)
+        .collect::<Result<Sequence, _>>()?;
+    if keys.is_empty() {
+        return Err("empty key binding".to_string());
+    }
+    Ok(keys)
+}
+
+pub fn show_sequence(keys: &[KeyPress]) -> String {
+    keys.iter()
+        .map(|key| key.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// What the keys pressed so far add up to
+pub enum Lookup {
+    Action(Action),
+    // The start of a longer binding, so wait for the next key
+    Pending,
+    Unbound,
+}
+
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<(Context, Bindings)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = CONTEXTS
+            .iter()
+            .map(|(context, _)| {
+                let actions = defaults(*context)
+                    .into_iter()
+                    .map(|(action, keys)| {
+                        let sequences = keys
+                            .into_iter()
+                            .map(|keys| parse_sequence(keys).expect("Invalid default key binding"))
+                            .collect();
+                        (action, sequences)
+                    })
+                    .collect();
+                (*context, actions)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    // The defaults with the config file's `[keys.<context>]` tables laid over them. Each action
+    // listed replaces that action's default keys. Every problem is reported, not just the first
+    pub fn load(overrides: &BTreeMap<String, toml::Value>) -> Result<Self, Vec<String>> {
+        let mut keymap = Self::default();
+        let mut errors = Vec::new();
+        for (context_name, actions) in overrides {
+            let context = match Context::from_name(context_name) {
+                Some(context) => context,
+                None => {
+                    errors.push(format!("unknown section [keys.{}]", context_name));
+                    continue;
+                }
+            };
+            let actions = match actions.as_table() {
+                Some(actions) => actions,
+                None => {
+                    errors.push(format!("[keys.{}] should be a table", context_name));
+                    continue;
+                }
+            };
+            for (action_name, keys) in actions {
+                let keys = match keys.clone().try_into::<Vec<String>>() {
+                    Ok(keys) => keys,
+                    Err(_) => {
+                        errors.push(format!(
+                            "{}: \"{}\" should be a list of keys, e.g. [\"g g\"]",
+                            context_name, action_name
+                        ));
+                        continue;
+                    }
+                };
+                let action = match Action::from_name(action_name) {
+                    Some(action) => action,
+                    None => {
+                        errors.push(format!(
+                            "{}: unknown action \"{}\"",
+                            context_name, action_name
+                        ));
+                        continue;
+                    }
+                };
+                let sequences = keys
+                    .iter()
+                    .map(|keys| parse_sequence(keys))
+                    .collect::<Result<Vec<_>, _>>();
+                match (sequences, keymap.bindings_mut(context, action)) {
+                    (Ok(sequences), Some(bound)) => *bound = sequences,
+                    (Err(error), _) => errors.push(format!("{}: {}", context_name, error)),
+                    (_, None) => errors.push(format!(
+                        "{}: \"{}\" can't be used here",
+                        context_name, action_name
+                    )),
+                }
+            }
+        }
+        for (context, actions) in keymap.bindings.iter() {
+            errors.extend(conflicts(*context, actions));
+        }
+        if errors.is_empty() {
+            Ok(keymap)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn bindings_mut(&mut self, context: Context, action: Action) -> Option<&mut Vec<Sequence>> {
+        self.bindings
+            .iter_mut()
+            .find(|(c, _)| *c == context)?
+            .1
+            .iter_mut()
+            .find(|(a, _)| *a == action)
+            .map(|(_, sequences)| sequences)
+    }
+
+    pub fn actions(&self, context: Context) -> &[(Action, Vec<Sequence>)] {
+        self.bindings
+            .iter()
+            .find(|(c, _)| *c == context)
+            .map_or(&[], |(_, actions)| actions.as_slice())
+    }
+
+    pub fn lookup(&self, context: Context, keys: &[KeyPress]) -> Lookup {
+        let mut pending = false;
+        for (action, sequences) in self.actions(context) {
+            for sequence in sequences {
+                if sequence.as_slice() == keys {
+                    return Lookup::Action(*action);
+                }
+                pending |= sequence.starts_with(keys);
+            }
+        }
+        if pending {
+            Lookup::Pending
+        } else {
+            Lookup::Unbound
+        }
+    }
+
+    // The first key bound to an action, written the way the config file would
+    pub fn key_for(&self, context: Context, action: Action) -> Option<String> {
+        self.actions(context)
+            .iter()
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, sequences)| sequences.first())
+            .map(|keys| show_sequence(keys))
+    }
+}
+
+// Two actions on the same keys, or one action's keys starting another's, can't both be reached
+fn conflicts(context: Context, actions: &[(Action, Vec<Sequence>)]) -> Vec<String> {
+    let mut errors = Vec::new();
+    let all: Vec<(Action, &Sequence)> = actions
+        .iter()
+        .flat_map(|(action, sequences)| sequences.iter().map(move |keys| (*action, keys)))
+        .collect();
+    for (index, (action, keys)) in all.iter().enumerate() {
+        for (other, other_keys) in all[index + 1..].iter() {
+            if action == other {
+                continue;
+            }
+            let (shorter, longer) = if keys.len() <= other_keys.len() {
+                (keys, other_keys)
+            } else {
+                (other_keys, keys)
+            };
+            if longer.starts_with(shorter) {
+                errors.push(format!(
+                    "{}: \"{}\" ({}) and \"{}\" ({}) clash",
+                    context.name(),
+                    show_sequence(keys),
+                    action.name(),
+                    show_sequence(other_keys),
+                    other.name()
+                ));
+            }
+        }
+    }
+    errors
+}
+
+impl App<'_> {
+    pub fn key_context(&self) -> Context {
+        if self.popup.is_some() {
+            Context::Popup
+        } else {
+            self.screen_context()
+        }
+    }
+
+    // The context of the screen itself, whether or not there's a popup over it
+    pub fn screen_context(&self) -> Context {
+        match self.current_screen {
+            CurrentScreen::Main(true) => Context::Filter,
+            CurrentScreen::Main(false) => Context::Main,
+            CurrentScreen::ViewingDay if self.read_mode => Context::Read,
+            CurrentScreen::ViewingDay => Context::Day,
+            CurrentScreen::Projects => Context::Projects,
+            CurrentScreen::People => Context::People,
+            CurrentScreen::Calendar => Context::Calendar,
+            CurrentScreen::Heatmap => Context::Heatmap,
+            CurrentScreen::SearchResults => Context::SearchResults,
+            CurrentScreen::Stats => Context::Stats,
+        }
+    }
+
+    // Whether an action does anything right now, so the footer only offers what will work
+    pub fn action_available(&self, action: Action) -> bool {
+        self.action_available_in(self.screen_context(), action)
+    }
+
+    // The same for an action on another screen, as if it were open
+    pub fn action_available_in(&self, context: Context, action: Action) -> bool {
+        match action {
+            Action::ClearFilter if context == Context::Main => self.filter.is_some(),
+            Action::MatchingLines => self.query.is_some(),
+            Action::Today if context == Context::Main => self.filter.is_none(),
+            Action::AssignProject => !self.config.projects.is_empty(),
+            Action::Metadata => !self.config.fields.is_empty(),
+            Action::UndoReplace => !self.replace_undo.is_empty(),
+            Action::Complete => !self.mention_completions().is_empty(),
+            _ => true,
+        }
+    }
+
+    // Actions that mean something in the popup that's open, in the order they're hinted
+    pub fn popup_actions(&self) -> Vec<Action> {
+        match &self.popup {
+            Some(Popup::ConfDeleteDay) | Some(Popup::ConfCreateDay(_)) => {
+                vec![Action::Confirm, Action::Back]
+            }
+            Some(Popup::Info(_)) => vec![Action::Back],
+            Some(Popup::Config(false)) => vec![Action::Edit, Action::Back],
+            Some(Popup::ProjectPicker(_)) => {
+                vec![
+                    Action::Back,
+                    Action::MoveUp,
+                    Action::MoveDown,
+                    Action::Select,
+                ]
+            }
+            Some(Popup::Metadata(_, false)) => vec![
+                Action::Back,
+                Action::MoveUp,
+                Action::MoveDown,
+                Action::Edit,
+                Action::Delete,
+            ],
+            Some(Popup::SavedSearches(_, false)) => {
+                let mut actions = vec![
+                    Action::Back,
+                    Action::MoveUp,
+                    Action::MoveDown,
+                    Action::Select,
+                    Action::Delete,
+                ];
+                if self.filter.is_some() && !self.fuzzy {
+                    actions.push(Action::Add);
+                }
+                actions
+            }
+            Some(Popup::ReplacePreview(_)) => vec![
+                Action::Back,
+                Action::MoveDown,
+                Action::MoveUp,
+                Action::Confirm,
+            ],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(text: &str) -> BTreeMap<String, toml::Value> {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn parses_modifiers_and_named_keys() {
+        let key = KeyPress::parse("ctrl-alt-x").unwrap();
+        assert_eq!(key.code, KeyCode::Char('x'));
+        assert_eq!(key.modifiers, KeyModifiers::CONTROL | KeyModifiers::ALT);
+        assert_eq!(KeyPress::parse("F5").unwrap().code, KeyCode::F(5));
+        assert_eq!(KeyPress::parse("pagedown").unwrap().code, KeyCode::PageDown);
+        assert!(KeyPress::parse("f13").is_err());
+        assert!(KeyPress::parse("nope").is_err());
+        assert!(parse_sequence("  ").is_err());
+    }
+
+    #[test]
+    fn shift_with_a_letter_is_the_capital() {
+        assert_eq!(
+            KeyPress::parse("shift-a").unwrap(),
+            KeyPress::parse("A").unwrap()
+        );
+        let typed = KeyPress::from(KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT));
+        assert_eq!(KeyPress::parse("shift-a").unwrap(), typed);
+    }
+
+    #[test]
+    fn wrong_types_are_reported_not_fatal() {
+        let errors = Keymap::load(&overrides("main = 3\n[filter]\ndone = \"enter\"\n"))
+            .err()
+            .unwrap();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("filter: \"done\" should be a list"));
+        assert_eq!(errors[1], "[keys.main] should be a table");
+    }
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let keymap = Keymap::load(&overrides("[main]\ntop = [\"t\"]\n")).unwrap();
+        let t = [KeyPress::parse("t").unwrap()];
+        assert!(matches!(
+            keymap.lookup(Context::Main, &t),
+            Lookup::Action(Action::Top)
+        ));
+        let g = [KeyPress::parse("g").unwrap()];
+        assert!(matches!(keymap.lookup(Context::Main, &g), Lookup::Unbound));
+    }
+}
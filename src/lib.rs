@@ -1,6 +1,28 @@
 pub mod app;
 pub mod arg;
+pub mod calendar;
 pub mod config;
+pub mod daterange;
+pub mod fuzzy;
+pub mod highlight;
+pub mod index;
+pub mod keymap;
+pub mod link;
+pub mod markdown;
+pub mod metadata;
+pub mod mouse;
+pub mod palette;
+pub mod people;
+pub mod project;
+pub mod query;
+pub mod replace;
+pub mod search;
+pub mod stats;
+pub mod tag;
+pub mod template;
+pub mod theme;
 pub mod tui;
 pub mod ui;
 pub mod update;
+pub mod vim;
+pub mod wrap;
@@ -0,0 +1,161 @@
+use chrono::NaiveDate;
+
+use crate::app::{Day, Days};
+
+const LINK_DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub date: NaiveDate,
+    pub heading: Option<String>,
+}
+
+impl Link {
+    fn parse(target: &str) -> Option<Self> {
+        let (date, heading) = match target.split_once('#') {
+            Some((date, heading)) => (date, Some(heading.trim().to_string())),
+            None => (target, None),
+        };
+        let date = NaiveDate::parse_from_str(date.trim(), LINK_DATE_FORMAT).ok()?;
+        Some(Self {
+            date,
+            heading: heading.filter(|h| !h.is_empty()),
+        })
+    }
+}
+
+// `[[2023-12-17]]` or `[[2023-12-17#Some heading]]`, with the char range they cover so the one
+// under the cursor can be found
+pub fn links(line: &str) -> Vec<(Link, usize, usize)> {
+    let mut links = Vec::new();
+    let mut rest = line;
+    let mut offset = 0;
+    while let Some(start) = rest.find("[[") {
+        let after = &rest[start + 2..];
+        let end = match after.find("]]") {
+            Some(end) => end,
+            None => break,
+        };
+        let target = &after[..end];
+        // An unclosed `[[` only hides links after it if we skip to its `]]`, so look again
+        // from the next bracket instead
+        if target.contains('[') {
+            offset += start + 1;
+            rest = &rest[start + 1..];
+            continue;
+        }
+        if let Some(link) = Link::parse(target) {
+            let from = line[..offset + start].chars().count();
+            let to = from + target.chars().count() + 4;
+            links.push((link, from, to));
+        }
+        let consumed = start + 2 + end + 2;
+        offset += consumed;
+        rest = &rest[consumed..];
+    }
+    links
+}
+
+// Prefer the link under the cursor, then the next one on the line, then the previous one
+pub fn link_at(line: &str, col: usize) -> Option<Link> {
+    let links = links(line);
+    links
+        .iter()
+        .find(|(_, from, to)| (*from..*to).contains(&col))
+        .or_else(|| links.iter().find(|(_, from, _)| *from >= col))
+        .or_else(|| links.last())
+        .map(|(link, _, _)| link.clone())
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let text = trimmed.trim_start_matches('#');
+    if text.len() == trimmed.len() || !text.starts_with(' ') {
+        return None;
+    }
+    Some(text.trim())
+}
+
+impl Day {
+    pub fn heading_row(&self, heading: &str) -> Option<usize> {
+        self.content
+            .iter()
+            .position(|line| heading_text(line).is_some_and(|h| h.eq_ignore_ascii_case(heading)))
+    }
+}
+
+impl Days {
+    // Every line in another day that links to `date`, newest first
+    pub fn backlinks(&self, date: NaiveDate) -> Vec<(NaiveDate, &str)> {
+        self.iter()
+            .filter(|d| d.date != date)
+            .flat_map(|d| {
+                d.content
+                    .iter()
+                    .filter(move |line| links(line).iter().any(|(link, _, _)| link.date == date))
+                    .map(move |line| (d.date, line.as_str()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn finds_links_with_their_char_ranges() {
+        let found = links("é [[2024-01-02]] and [[2024-01-03#Notes ]]");
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0],
+            (
+                Link {
+                    date: date(2),
+                    heading: None
+                },
+                2,
+                16
+            )
+        );
+        assert_eq!(found[1].0.date, date(3));
+        assert_eq!(found[1].0.heading.as_deref(), Some("Notes"));
+    }
+
+    #[test]
+    fn skips_targets_that_are_not_dates() {
+        assert!(links("[[not a date]] [[2024-13-01]] [[]] [[2024-01-02").is_empty());
+        assert!(links("").is_empty());
+        assert_eq!(links("[[2024-01-02#]]")[0].0.heading, None);
+    }
+
+    #[test]
+    fn an_unclosed_bracket_does_not_hide_a_later_link() {
+        let found = links("[[oops [[2024-01-02]]");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0.date, date(2));
+        assert_eq!((found[0].1, found[0].2), (7, 21));
+        assert_eq!(links("[[[2024-01-02]]").len(), 1);
+    }
+
+    #[test]
+    fn link_at_prefers_the_one_under_the_cursor() {
+        let line = "[[2024-01-01]] then [[2024-01-02]]";
+        assert_eq!(link_at(line, 3).unwrap().date, date(1));
+        assert_eq!(link_at(line, 15).unwrap().date, date(2));
+        assert_eq!(link_at(line, 40).unwrap().date, date(2));
+        assert_eq!(link_at("no links", 0), None);
+    }
+
+    #[test]
+    fn heading_rows_ignore_case_and_need_a_space() {
+        let mut day = Day::new(date(1));
+        day.content = vec!["#tag".to_string(), "## Notes".to_string()];
+        assert_eq!(day.heading_row("notes"), Some(1));
+        assert_eq!(day.heading_row("tag"), None);
+    }
+}
@@ -19,6 +19,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         confy::load("englog", None).expect("Failed to load new config file")
     });
 
+    englog::highlight::preload();
+
     let mut tui = Tui::new(Terminal::new(CrosstermBackend::new(io::stdout()))?);
 
     tui.enter()?;
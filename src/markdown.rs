@@ -0,0 +1,296 @@
+use ratatui::{
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+};
+
+use crate::highlight::code_spans;
+use crate::theme::Theme;
+
+pub const FENCE: &str = "```";
+
+// `**bold**`, `__bold__`, `*italic*`, `_italic_` and `` `code` `` within a line
+fn inline(text: &str, base: Style, theme: &Theme) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let marker = match c {
+            '`' => "`",
+            '*' if rest.starts_with("**") => "**",
+            '_' if rest.starts_with("__") => "__",
+            '*' | '_' => &rest[..1],
+            _ => "",
+        };
+        // Markers only count when they're closed later on the same line and wrap something.
+        // Emphasis can't start or end with a space, so `2 * 3 * 4` stays as it is
+        let closing = (!marker.is_empty())
+            .then(|| rest[marker.len()..].find(marker))
+            .flatten()
+            .filter(|end| {
+                let inner = &rest[marker.len()..marker.len() + end];
+                !inner.is_empty() && (marker == "`" || inner.trim() == inner)
+            });
+        // `snake_case` words shouldn't turn italic
+        let mid_word = marker == "_" && plain.chars().last().is_some_and(char::is_alphanumeric);
+        match closing {
+            Some(end) if !mid_word => {
+                if !plain.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut plain), base));
+                }
+                let inner = &rest[marker.len()..marker.len() + end];
+                // Code is shown as typed, emphasis can have more markup inside it
+                match marker {
+                    "`" => spans.push(Span::styled(inner.to_string(), theme.code())),
+                    "**" | "__" => spans.extend(inline(inner, base.bold(), theme)),
+                    _ => spans.extend(inline(inner, base.italic(), theme)),
+                }
+                rest = &rest[marker.len() * 2 + end..];
+            }
+            _ => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() || spans.is_empty() {
+        spans.push(Span::styled(plain, base));
+    }
+    spans
+}
+
+pub fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level, text))
+}
+
+// `1. item` or `1) item`, split into the number with its punctuation and the rest
+pub fn numbered_item(line: &str) -> Option<(&str, &str)> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let after = &line[digits..];
+    if digits == 0 || !(after.starts_with(". ") || after.starts_with(") ")) {
+        return None;
+    }
+    Some((&line[..digits + 1], &after[2..]))
+}
+
+fn render_line(line: &str, theme: &Theme) -> Line<'static> {
+    let trimmed = line.trim_start();
+    let indent = line[..line.len() - trimmed.len()].to_string();
+    let base = theme.text();
+
+    if let Some((level, text)) = heading(trimmed) {
+        let style = match level {
+            1 => base.patch(theme.heading()).underlined(),
+            2 => base.patch(theme.heading()),
+            _ => base.bold(),
+        };
+        return Line::from(inline(text, style, theme));
+    }
+    if let Some(quote) = trimmed.strip_prefix('>') {
+        let style = base.patch(theme.muted()).italic();
+        let mut spans = vec![Span::styled(format!("{}│ ", indent), style)];
+        spans.extend(inline(quote.trim_start(), style, theme));
+        return Line::from(spans);
+    }
+    let bullet = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker));
+    if let Some(item) = bullet {
+        let (marker, item) = if let Some(item) = item.strip_prefix("[ ] ") {
+            ("☐ ", item)
+        } else if let Some(item) = item
+            .strip_prefix("[x] ")
+            .or_else(|| item.strip_prefix("[X] "))
+        {
+            ("☑ ", item)
+        } else {
+            ("• ", item)
+        };
+        let mut spans = vec![Span::styled(
+            format!("{}{}", indent, marker),
+            base.patch(theme.marker()),
+        )];
+        spans.extend(inline(item, base, theme));
+        return Line::from(spans);
+    }
+    if let Some((number, item)) = numbered_item(trimmed) {
+        let mut spans = vec![Span::styled(
+            format!("{}{} ", indent, number),
+            base.patch(theme.marker()).bold(),
+        )];
+        spans.extend(inline(item, base, theme));
+        return Line::from(spans);
+    }
+    let mut spans = inline(trimmed, base, theme);
+    if !indent.is_empty() {
+        spans.insert(0, Span::raw(indent));
+    }
+    Line::from(spans)
+}
+
+// Day content styled for reading. The text itself is left alone apart from the markup
+pub fn render(lines: &[String], theme: &Theme) -> Text<'static> {
+    let mut rendered = Vec::new();
+    let mut in_code = false;
+    let highlighted = if theme.plain {
+        vec![None; lines.len()]
+    } else {
+        code_spans(lines, &theme.code_theme, 0..lines.len())
+    };
+    for (line, code) in lines.iter().zip(highlighted) {
+        if line.trim_start().starts_with(FENCE) {
+            in_code = !in_code;
+            rendered.push(Line::from(Span::styled(line.clone(), theme.muted())));
+        } else if let Some(code) = code {
+            let spans: Vec<Span> = code
+                .into_iter()
+                .map(|(style, text)| Span::styled(text, style))
+                .collect();
+            rendered.push(Line::from(spans));
+        } else if in_code {
+            rendered.push(Line::from(Span::styled(line.clone(), theme.code())));
+        } else {
+            rendered.push(render_line(line, theme));
+        }
+    }
+    Text::from(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Modifier;
+
+    fn base() -> Style {
+        Theme::dark().text()
+    }
+
+    fn parts(spans: &[Span]) -> Vec<(String, Style)> {
+        spans
+            .iter()
+            .map(|span| (span.content.to_string(), span.style))
+            .collect()
+    }
+
+    fn plain(text: &str) -> Vec<(String, Style)> {
+        vec![(text.to_string(), base())]
+    }
+
+    #[test]
+    fn styles_inline_markup() {
+        assert_eq!(
+            parts(&inline("a **b** __c__ *d* _e_ `f`", base(), &Theme::dark())),
+            vec![
+                ("a ".to_string(), base()),
+                ("b".to_string(), base().bold()),
+                (" ".to_string(), base()),
+                ("c".to_string(), base().bold()),
+                (" ".to_string(), base()),
+                ("d".to_string(), base().italic()),
+                (" ".to_string(), base()),
+                ("e".to_string(), base().italic()),
+                (" ".to_string(), base()),
+                ("f".to_string(), Theme::dark().code()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_markers_stay_as_text() {
+        for text in ["a **b", "`code", "a * b * c", "snake_case_name", "** **"] {
+            assert_eq!(
+                parts(&inline(text, base(), &Theme::dark())),
+                plain(text),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(parts(&inline("", base(), &Theme::dark())), plain(""));
+        // An unmatched `**` can still close a single `*`, as in CommonMark
+        assert_eq!(
+            parts(&inline("**b*", base(), &Theme::dark())),
+            vec![
+                ("*".to_string(), base()),
+                ("b".to_string(), base().italic())
+            ]
+        );
+    }
+
+    #[test]
+    fn emphasis_nests_but_code_does_not() {
+        assert_eq!(
+            parts(&inline("**bold _both_**", base(), &Theme::dark())),
+            vec![
+                ("bold ".to_string(), base().bold()),
+                (
+                    "both".to_string(),
+                    base().add_modifier(Modifier::BOLD | Modifier::ITALIC)
+                ),
+            ]
+        );
+        assert_eq!(
+            parts(&inline("`**not bold**`", base(), &Theme::dark())),
+            vec![("**not bold**".to_string(), Theme::dark().code())]
+        );
+    }
+
+    #[test]
+    fn reads_headings_and_numbered_items() {
+        assert_eq!(heading("## Title"), Some((2, "Title")));
+        assert_eq!(heading("###### Six"), Some((6, "Six")));
+        for line in ["#tag", "####### Seven", "#", "Not # one"] {
+            assert_eq!(heading(line), None, "{}", line);
+        }
+        assert_eq!(numbered_item("1. one"), Some(("1.", "one")));
+        assert_eq!(numbered_item("12) twelve"), Some(("12)", "twelve")));
+        for line in ["1.5 kg", ". x", "1.", "x. y"] {
+            assert_eq!(numbered_item(line), None, "{}", line);
+        }
+    }
+
+    fn rendered(lines: &[&str]) -> Vec<String> {
+        let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        render(&lines, &Theme::dark())
+            .lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn renders_lists_quotes_and_code() {
+        assert_eq!(
+            rendered(&[
+                "# **Day**",
+                "  - [ ] todo",
+                "- [x] done",
+                "> said *so*",
+                "```",
+                "**as typed**",
+                "```",
+                "3) three",
+            ]),
+            vec![
+                "Day",
+                "  ☐ todo",
+                "☑ done",
+                "│ said so",
+                "```",
+                "**as typed**",
+                "```",
+                "3) three",
+            ]
+        );
+        // An unclosed fence leaves the rest as code
+        assert_eq!(
+            rendered(&["```", "# not a heading"]),
+            vec!["```", "# not a heading"]
+        );
+    }
+}
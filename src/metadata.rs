@@ -0,0 +1,393 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::app::Day;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    Number,
+    Bool,
+    Enum,
+    Text,
+}
+
+// One entry of the metadata schema in the config file, e.g.
+// [[fields]]
+// name = "location"
+// kind = "enum"
+// options = ["wfh", "office"]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FieldDef {
+    pub name: String,
+    pub kind: FieldKind,
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum MetaValue {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl std::fmt::Display for MetaValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetaValue::Number(n) => write!(f, "{}", n),
+            MetaValue::Bool(true) => write!(f, "yes"),
+            MetaValue::Bool(false) => write!(f, "no"),
+            MetaValue::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl FieldDef {
+    pub fn parse(&self, input: &str) -> Option<MetaValue> {
+        let input = input.trim();
+        match self.kind {
+            FieldKind::Number => input.parse().ok().map(MetaValue::Number),
+            FieldKind::Bool => parse_bool(input).map(MetaValue::Bool),
+            FieldKind::Enum => self
+                .options
+                .iter()
+                .find(|o| o.eq_ignore_ascii_case(input))
+                .map(|o| MetaValue::Text(o.clone())),
+            FieldKind::Text if input.is_empty() => None,
+            FieldKind::Text => Some(MetaValue::Text(input.to_string())),
+        }
+    }
+
+    // Bools flip, enums move to the next option. Other kinds need typing
+    pub fn cycle(&self, current: Option<&MetaValue>) -> Option<MetaValue> {
+        match (self.kind, current) {
+            (FieldKind::Bool, Some(MetaValue::Bool(b))) => Some(MetaValue::Bool(!b)),
+            (FieldKind::Bool, _) => Some(MetaValue::Bool(true)),
+            (FieldKind::Enum, current) => {
+                let next = match current {
+                    Some(MetaValue::Text(text)) => self
+                        .options
+                        .iter()
+                        .position(|o| o == text)
+                        .map_or(0, |i| (i + 1) % self.options.len()),
+                    _ => 0,
+                };
+                self.options.get(next).cloned().map(MetaValue::Text)
+            }
+            _ => None,
+        }
+    }
+
+    // Column width in the day list
+    pub fn width(&self) -> usize {
+        self.name.chars().count().max(5)
+    }
+}
+
+fn parse_bool(input: &str) -> Option<bool> {
+    match input.to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" | "on" => Some(true),
+        "false" | "no" | "n" | "0" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Comparison {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+// Splits `hours>=6` into ("hours", Ge, "6")
+pub fn split_field_query(term: &str) -> Option<(&str, Comparison, &str)> {
+    let index = term.find([':', '<', '>', '='])?;
+    let (name, rest) = term.split_at(index);
+    let (comparison, value) = if let Some(value) = rest.strip_prefix(">=") {
+        (Comparison::Ge, value)
+    } else if let Some(value) = rest.strip_prefix("<=") {
+        (Comparison::Le, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Comparison::Gt, value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Comparison::Lt, value)
+    } else {
+        (Comparison::Eq, &rest[1..])
+    };
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, comparison, value))
+}
+
+impl MetaValue {
+    pub fn compare(&self, comparison: Comparison, value: &str) -> bool {
+        match self {
+            MetaValue::Number(n) => match value.trim().parse::<f64>() {
+                Ok(value) => match comparison {
+                    Comparison::Eq => *n == value,
+                    Comparison::Lt => *n < value,
+                    Comparison::Le => *n <= value,
+                    Comparison::Gt => *n > value,
+                    Comparison::Ge => *n >= value,
+                },
+                Err(_) => false,
+            },
+            MetaValue::Bool(b) => {
+                comparison == Comparison::Eq && parse_bool(value).is_some_and(|v| v == *b)
+            }
+            MetaValue::Text(text) => {
+                comparison == Comparison::Eq && text.to_lowercase().contains(&value.to_lowercase())
+            }
+        }
+    }
+}
+
+impl Day {
+    // Field names don't care about case, here the same as in filters
+    pub fn meta_value(&self, name: &str) -> Option<&MetaValue> {
+        self.meta
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn meta_matches(&self, name: &str, comparison: Comparison, value: &str) -> bool {
+        self.meta_value(name)
+            .is_some_and(|v| v.compare(comparison, value))
+    }
+}
+
+pub enum Summary {
+    Number {
+        count: usize,
+        total: f64,
+        min: f64,
+        max: f64,
+    },
+    Bool {
+        count: usize,
+        yes: usize,
+    },
+    Counts {
+        count: usize,
+        values: Vec<(String, usize)>,
+    },
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Summary::Number { count: 0, .. }
+            | Summary::Bool { count: 0, .. }
+            | Summary::Counts { count: 0, .. } => write!(f, "no values"),
+            Summary::Number {
+                count,
+                total,
+                min,
+                max,
+            } => write!(
+                f,
+                "total {} | avg {:.1} | min {} | max {} ({} days)",
+                total,
+                total / *count as f64,
+                min,
+                max,
+                count
+            ),
+            Summary::Bool { count, yes } => write!(f, "yes on {} of {} days", yes, count),
+            Summary::Counts { count, values } => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|(value, n)| format!("{}: {}", value, n))
+                    .collect();
+                write!(f, "{} ({} days)", values.join(" | "), count)
+            }
+        }
+    }
+}
+
+pub fn summarise<'a>(field: &FieldDef, days: impl Iterator<Item = &'a Day>) -> Summary {
+    let values = days.filter_map(|d| d.meta_value(&field.name));
+    match field.kind {
+        FieldKind::Number => {
+            let numbers: Vec<f64> = values
+                .filter_map(|v| match v {
+                    MetaValue::Number(n) => Some(*n),
+                    _ => None,
+                })
+                .collect();
+            Summary::Number {
+                count: numbers.len(),
+                total: numbers.iter().sum(),
+                min: numbers.iter().copied().fold(f64::INFINITY, f64::min),
+                max: numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            }
+        }
+        FieldKind::Bool => {
+            let bools: Vec<bool> = values
+                .filter_map(|v| match v {
+                    MetaValue::Bool(b) => Some(*b),
+                    _ => None,
+                })
+                .collect();
+            Summary::Bool {
+                count: bools.len(),
+                yes: bools.iter().filter(|b| **b).count(),
+            }
+        }
+        FieldKind::Enum | FieldKind::Text => {
+            let mut counts: Vec<(String, usize)> = Vec::new();
+            for value in values.map(|v| v.to_string()) {
+                match counts.iter_mut().find(|(v, _)| *v == value) {
+                    Some((_, n)) => *n += 1,
+                    None => counts.push((value, 1)),
+                }
+            }
+            counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+            Summary::Counts {
+                count: counts.iter().map(|(_, n)| n).sum(),
+                values: counts,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(kind: FieldKind, options: &[&str]) -> FieldDef {
+        FieldDef {
+            name: "field".to_string(),
+            kind,
+            options: options.iter().map(|o| o.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn splits_on_the_first_operator() {
+        assert_eq!(
+            split_field_query("hours>=6"),
+            Some(("hours", Comparison::Ge, "6"))
+        );
+        assert_eq!(
+            split_field_query("hours<=6"),
+            Some(("hours", Comparison::Le, "6"))
+        );
+        assert_eq!(
+            split_field_query("hours>6"),
+            Some(("hours", Comparison::Gt, "6"))
+        );
+        assert_eq!(
+            split_field_query("hours<6"),
+            Some(("hours", Comparison::Lt, "6"))
+        );
+        assert_eq!(
+            split_field_query("hours=6"),
+            Some(("hours", Comparison::Eq, "6"))
+        );
+        assert_eq!(
+            split_field_query("location:office"),
+            Some(("location", Comparison::Eq, "office"))
+        );
+        assert_eq!(
+            split_field_query("note:a:b"),
+            Some(("note", Comparison::Eq, "a:b"))
+        );
+    }
+
+    #[test]
+    fn needs_a_name_and_an_operator() {
+        assert_eq!(split_field_query(""), None);
+        assert_eq!(split_field_query("plain"), None);
+        assert_eq!(split_field_query(">=6"), None);
+        assert_eq!(split_field_query(":office"), None);
+        assert_eq!(
+            split_field_query("hours:"),
+            Some(("hours", Comparison::Eq, ""))
+        );
+    }
+
+    #[test]
+    fn parses_values_by_kind() {
+        assert_eq!(
+            field(FieldKind::Number, &[]).parse(" 7.5 "),
+            Some(MetaValue::Number(7.5))
+        );
+        assert_eq!(field(FieldKind::Number, &[]).parse("seven"), None);
+        assert_eq!(
+            field(FieldKind::Bool, &[]).parse("Yes"),
+            Some(MetaValue::Bool(true))
+        );
+        assert_eq!(field(FieldKind::Bool, &[]).parse("maybe"), None);
+        assert_eq!(
+            field(FieldKind::Enum, &["wfh", "office"]).parse("WFH"),
+            Some(MetaValue::Text("wfh".to_string()))
+        );
+        assert_eq!(field(FieldKind::Enum, &["wfh"]).parse("home"), None);
+        assert_eq!(field(FieldKind::Text, &[]).parse("  "), None);
+    }
+
+    #[test]
+    fn compares_by_kind() {
+        let hours = MetaValue::Number(6.0);
+        assert!(hours.compare(Comparison::Ge, "6"));
+        assert!(!hours.compare(Comparison::Gt, "6"));
+        assert!(!hours.compare(Comparison::Eq, "six"));
+        assert!(MetaValue::Bool(false).compare(Comparison::Eq, "no"));
+        assert!(!MetaValue::Bool(false).compare(Comparison::Lt, "yes"));
+        assert!(MetaValue::Text("Office".to_string()).compare(Comparison::Eq, "off"));
+    }
+
+    #[test]
+    fn summaries_find_fields_whatever_their_case() {
+        let mut days = Vec::new();
+        for (i, (key, value)) in [("mood", 3.0), ("Mood", 5.0), ("MOOD", 1.0)]
+            .iter()
+            .enumerate()
+        {
+            let mut day = Day::with_lines((2024, 1, i as u32 + 1), &[]);
+            day.meta.insert(key.to_string(), MetaValue::Number(*value));
+            days.push(day);
+        }
+        let mood = FieldDef {
+            name: "mood".to_string(),
+            kind: FieldKind::Number,
+            options: Vec::new(),
+        };
+        assert_eq!(
+            summarise(&mood, days.iter()).to_string(),
+            "total 9 | avg 3.0 | min 1 | max 5 (3 days)"
+        );
+        assert!(days[1].meta_matches("MOOD", Comparison::Gt, "4"));
+        assert_eq!(days[2].meta_value("mood"), Some(&MetaValue::Number(1.0)));
+    }
+
+    #[test]
+    fn summaries_of_nothing() {
+        let days: Vec<Day> = Vec::new();
+        assert_eq!(
+            summarise(&field(FieldKind::Bool, &[]), days.iter()).to_string(),
+            "no values"
+        );
+    }
+
+    #[test]
+    fn enum_summaries_count_each_value() {
+        let days: Vec<Day> = ["wfh", "office", "wfh"]
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let mut day = Day::with_lines((2024, 1, i as u32 + 1), &[]);
+                day.meta
+                    .insert("Field".to_string(), MetaValue::Text(value.to_string()));
+                day
+            })
+            .collect();
+        let summary = summarise(&field(FieldKind::Enum, &["wfh", "office"]), days.iter());
+        assert_eq!(summary.to_string(), "wfh: 2 | office: 1 (3 days)");
+    }
+}
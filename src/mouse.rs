@@ -0,0 +1,45 @@
+use crossterm::event::KeyCode;
+use ratatui::layout::Rect;
+
+use crate::keymap::Action;
+
+// What clicking a popup button does: the key it stands for in popups that take text,
+// or the action it's labelled with everywhere else, whatever keys that's bound to
+#[derive(Debug, Clone, Copy)]
+pub enum Button {
+    Key(KeyCode),
+    Action(Action),
+}
+
+// Where things were drawn on the last frame, so clicks can be matched up with them
+#[derive(Default, Debug, Clone)]
+pub struct ClickAreas {
+    // Rows of the day list, not including its border
+    pub day_list: Option<Rect>,
+    pub scrollbar: Option<Rect>,
+    // Text inside the editor's border
+    pub editor: Option<Rect>,
+    pub buttons: Vec<(Rect, Button)>,
+}
+
+impl ClickAreas {
+    pub fn button_at(&self, column: u16, row: u16) -> Option<Button> {
+        self.buttons
+            .iter()
+            .find(|(rect, _)| contains(*rect, column, row))
+            .map(|(_, button)| *button)
+    }
+}
+
+pub fn contains(rect: Rect, column: u16, row: u16) -> bool {
+    column >= rect.x && column < rect.right() && row >= rect.y && row < rect.bottom()
+}
+
+// The item a point on the scrollbar stands for, with the top and bottom rows covering the ends
+pub fn scrollbar_position(rect: Rect, row: u16, count: usize) -> usize {
+    if count == 0 || rect.height <= 1 {
+        return 0;
+    }
+    let offset = row.saturating_sub(rect.y).min(rect.height - 1) as usize;
+    offset * (count - 1) / (rect.height as usize - 1)
+}
@@ -0,0 +1,81 @@
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+
+use crate::app::{App, CurrentScreen};
+use crate::keymap::{Action, Context};
+
+// A line in the command palette
+pub struct Command {
+    pub action: Action,
+    // The screen it belongs to, which is opened first when it isn't the one showing
+    pub context: Context,
+    // First key bound to it, if it has one
+    pub key: Option<String>,
+}
+
+impl App<'_> {
+    // Everything that can be done from here, whichever screen it's on. Best matches for the
+    // query come first, and ties keep the current screen's actions ahead of the rest, each
+    // in the order the keymap lists them
+    pub fn palette_commands(&self) -> Vec<Command> {
+        let current = self.screen_context();
+        let mut contexts = vec![current];
+        contexts.extend(
+            Context::all().filter(|context| *context != current && self.can_reach(*context)),
+        );
+        let mut candidates: Vec<(Context, Action)> = Vec::new();
+        for context in contexts {
+            for (action, _) in self.keymap.actions(context) {
+                if matches!(action, Action::Palette | Action::Complete)
+                    || !self.action_available_in(context, *action)
+                    || candidates.iter().any(|(_, listed)| listed == action)
+                {
+                    continue;
+                }
+                candidates.push((context, *action));
+            }
+        }
+
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let query = self.palette_query.trim();
+        let mut scored: Vec<(i64, usize, Context, Action)> = candidates
+            .into_iter()
+            .enumerate()
+            .filter_map(|(order, (context, action))| {
+                if query.is_empty() {
+                    return Some((0, order, context, action));
+                }
+                let text = format!(
+                    "{} {} {}",
+                    action.description(),
+                    action.name(),
+                    context.name()
+                );
+                matcher
+                    .fuzzy_match(&text, query)
+                    .map(|score| (score, order, context, action))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        scored
+            .into_iter()
+            .map(|(_, _, context, action)| Command {
+                action,
+                context,
+                key: self.keymap.key_for(context, action),
+            })
+            .collect()
+    }
+
+    // Whether the palette can open the screen for `context` to run something there
+    fn can_reach(&self, context: Context) -> bool {
+        match context {
+            Context::Day | Context::Read => match self.current_screen {
+                CurrentScreen::ViewingDay => true,
+                _ => self.selected_day().is_some(),
+            },
+            Context::SearchResults => self.query.is_some(),
+            Context::Popup => false,
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::NaiveDate;
+
+use crate::app::{Day, Days};
+
+pub const MENTION_PREFIX: char = '@';
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+// `@name` at the start of a word. Anything glued to the front (emails etc.) is ignored
+pub fn mentions(line: &str) -> Vec<&str> {
+    let mut mentions = Vec::new();
+    let mut previous: Option<char> = None;
+    for (index, c) in line.char_indices() {
+        let at_word_start = previous.is_none_or(|p| !is_name_char(p) && p != MENTION_PREFIX);
+        if c == MENTION_PREFIX && at_word_start {
+            let rest = &line[index + c.len_utf8()..];
+            let end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+            // Trailing punctuation belongs to the sentence, not the name
+            let name = rest[..end].trim_end_matches(['.', '-']);
+            if !name.is_empty() {
+                mentions.push(name);
+            }
+        }
+        previous = Some(c);
+    }
+    mentions
+}
+
+pub fn same_person(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+pub struct Person {
+    pub name: String,
+    // Days they're mentioned on, however many times that is on each
+    pub days: usize,
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+    pub lines: Vec<(NaiveDate, String)>,
+}
+
+impl Day {
+    pub fn mentions(&self) -> impl Iterator<Item = &str> {
+        self.content.iter().flat_map(|line| mentions(line))
+    }
+
+    pub fn mentions_person(&self, name: &str) -> bool {
+        self.mentions().any(|m| same_person(m, name))
+    }
+}
+
+impl Days {
+    // Most mentioned first, lines oldest first
+    pub fn people(&self) -> Vec<Person> {
+        let mut people: Vec<Person> = Vec::new();
+        for day in self.iter().rev() {
+            for line in day.content.iter() {
+                let mut seen_on_line: Vec<&str> = Vec::new();
+                for name in mentions(line) {
+                    if seen_on_line.iter().any(|n| same_person(n, name)) {
+                        continue;
+                    }
+                    seen_on_line.push(name);
+                    match people.iter_mut().find(|p| same_person(&p.name, name)) {
+                        Some(person) => {
+                            if person.last != day.date {
+                                person.days += 1;
+                            }
+                            person.last = day.date;
+                            person.lines.push((day.date, line.clone()));
+                        }
+                        None => people.push(Person {
+                            name: name.to_string(),
+                            days: 1,
+                            first: day.date,
+                            last: day.date,
+                            lines: vec![(day.date, line.clone())],
+                        }),
+                    }
+                }
+            }
+        }
+        people.sort_by(|a, b| {
+            b.days
+                .cmp(&a.days)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        people
+    }
+}
+
+// The `@partial` word directly before the cursor, without the prefix
+pub fn mention_before_cursor(line: &str, col: usize) -> Option<&str> {
+    let end = line
+        .char_indices()
+        .nth(col)
+        .map_or(line.len(), |(index, _)| index);
+    let before = &line[..end];
+    let start = before
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_name_char(*c))?;
+    if start.1 != MENTION_PREFIX {
+        return None;
+    }
+    let preceding = before[..start.0].chars().last();
+    if preceding.is_some_and(is_name_char) {
+        return None;
+    }
+    Some(&before[start.0 + MENTION_PREFIX.len_utf8()..])
+}
+
+// Alphabetical, so the order doesn't jump about as people are mentioned more
+pub fn completions<'a>(people: &'a [Person], partial: &str) -> Vec<&'a str> {
+    let partial = partial.to_lowercase();
+    let mut names: Vec<&str> = people
+        .iter()
+        .map(|person| person.name.as_str())
+        .filter(|name| {
+            let name = name.to_lowercase();
+            name.starts_with(&partial) && name != partial
+        })
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_at_word_starts() {
+        assert_eq!(
+            mentions("@alice and @bob.smith."),
+            vec!["alice", "bob.smith"]
+        );
+        assert_eq!(mentions("(@carol) @dave-"), vec!["carol", "dave"]);
+    }
+
+    #[test]
+    fn ignores_emails_and_bare_prefixes() {
+        assert!(mentions("mail alice@example.com").is_empty());
+        assert!(mentions("@ @@ @. @-").is_empty());
+        assert!(mentions("").is_empty());
+    }
+
+    #[test]
+    fn mention_before_cursor_needs_a_prefix_at_a_word_start() {
+        assert_eq!(mention_before_cursor("hi @al", 6), Some("al"));
+        assert_eq!(mention_before_cursor("hi @", 4), Some(""));
+        assert_eq!(mention_before_cursor("hi @al there", 12), None);
+        assert_eq!(mention_before_cursor("me@al", 5), None);
+        assert_eq!(mention_before_cursor("", 0), None);
+    }
+
+    #[test]
+    fn people_counts_days_not_mentions() {
+        let days = Days {
+            days: vec![
+                Day::with_lines((2024, 1, 3), &["@Alice"]),
+                Day::with_lines((2024, 1, 2), &["@bob"]),
+                Day::with_lines((2024, 1, 1), &["@alice @alice", "@alice again", "@bob"]),
+            ],
+        };
+        let people = days.people();
+        assert_eq!(people.len(), 2);
+        assert_eq!(people[0].name, "alice");
+        assert_eq!(people[0].days, 2);
+        assert_eq!(people[0].lines.len(), 3);
+        assert_eq!(people[0].last, NaiveDate::from_ymd_opt(2024, 1, 3).unwrap());
+        assert_eq!(people[1].days, 2);
+    }
+
+    #[test]
+    fn completions_are_alphabetical_and_skip_exact_matches() {
+        let days = Days {
+            days: vec![Day::with_lines(
+                (2024, 1, 1),
+                &["@bob @Bea @ben @ben @alice"],
+            )],
+        };
+        let people = days.people();
+        assert_eq!(completions(&people, "b"), vec!["Bea", "ben", "bob"]);
+        assert_eq!(completions(&people, "BEN"), Vec::<&str>::new());
+    }
+}
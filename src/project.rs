@@ -0,0 +1,166 @@
+use chrono::NaiveDate;
+
+use crate::app::{Day, Days};
+
+// Lines starting with this marker assign everything below them (up to the next
+// marker) to the named project
+pub const PROJECT_MARKER: &str = "project:";
+
+pub fn marker_name(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let prefix = line.get(..PROJECT_MARKER.len())?;
+    if prefix.eq_ignore_ascii_case(PROJECT_MARKER) {
+        let name = line[PROJECT_MARKER.len()..].trim();
+        if !name.is_empty() {
+            return Some(name);
+        }
+    }
+    None
+}
+
+pub fn marker_line(project: &str) -> String {
+    format!("{} {}", PROJECT_MARKER, project)
+}
+
+pub fn same_project(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+impl Day {
+    pub fn projects(&self) -> Vec<&str> {
+        let mut projects: Vec<&str> = Vec::new();
+        for name in self.content.iter().filter_map(|l| marker_name(l)) {
+            if !projects.iter().any(|p| same_project(p, name)) {
+                projects.push(name);
+            }
+        }
+        projects
+    }
+
+    pub fn has_project(&self, project: &str) -> bool {
+        self.content
+            .iter()
+            .filter_map(|l| marker_name(l))
+            .any(|name| same_project(name, project))
+    }
+
+    // Every line between a marker for `project` and the next marker
+    pub fn project_lines(&self, project: &str) -> Vec<&str> {
+        let mut lines = Vec::new();
+        let mut in_project = false;
+        for line in self.content.iter() {
+            if let Some(name) = marker_name(line) {
+                in_project = same_project(name, project);
+                continue;
+            }
+            if in_project {
+                lines.push(line.as_str());
+            }
+        }
+        lines
+    }
+
+    // Claims whatever is above the first marker, as that belongs to no project yet. When
+    // there's nothing like that the marker starts a new section at the end of the day instead
+    // of an empty one in front of another project's
+    pub fn assign_project(&mut self, project: &str) {
+        if self.has_project(project) {
+            return;
+        }
+        let unassigned = self
+            .content
+            .iter()
+            .take_while(|line| marker_name(line).is_none())
+            .any(|line| !line.trim().is_empty());
+        if unassigned {
+            self.content.insert(0, marker_line(project));
+        } else {
+            self.content.push(marker_line(project));
+        }
+    }
+}
+
+impl Days {
+    // Oldest first, which is the opposite of how days are stored
+    pub fn project_timeline(&self, project: &str) -> Vec<(NaiveDate, Vec<&str>)> {
+        self.iter()
+            .rev()
+            .filter(|d| d.has_project(project))
+            .map(|d| (d.date, d.project_lines(project)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers_are_case_insensitive_and_need_a_name() {
+        assert_eq!(marker_name("  Project: Alpha "), Some("Alpha"));
+        assert_eq!(marker_name("PROJECT:beta"), Some("beta"));
+        assert_eq!(marker_name("project:   "), None);
+        assert_eq!(marker_name("my project: alpha"), None);
+        assert!(same_project(" alpha", "ALPHA "));
+    }
+
+    #[test]
+    fn project_lines_run_to_the_next_marker() {
+        let day = Day::with_lines(
+            (2024, 1, 2),
+            &[
+                "intro",
+                "project: alpha",
+                "a1",
+                "project: beta",
+                "b1",
+                "Project: Alpha",
+                "a2",
+            ],
+        );
+        assert_eq!(day.project_lines("alpha"), vec!["a1", "a2"]);
+        assert_eq!(day.project_lines("beta"), vec!["b1"]);
+        assert!(day.project_lines("gamma").is_empty());
+        assert_eq!(day.projects(), vec!["alpha", "beta"]);
+    }
+
+    #[test]
+    fn assigning_claims_unassigned_lines() {
+        let mut day = Day::with_lines((2024, 1, 2), &["", "fixed the build"]);
+        day.assign_project("alpha");
+        assert_eq!(day.content[0], "project: alpha");
+        assert_eq!(day.project_lines("alpha"), vec!["", "fixed the build"]);
+
+        day.assign_project("ALPHA");
+        assert_eq!(day.content.len(), 3);
+    }
+
+    #[test]
+    fn assigning_after_another_project_adds_a_section_at_the_end() {
+        let mut day = Day::with_lines((2024, 1, 2), &["project: alpha", "a1"]);
+        day.assign_project("beta");
+        assert_eq!(day.content, vec!["project: alpha", "a1", "project: beta"]);
+        assert_eq!(day.project_lines("alpha"), vec!["a1"]);
+        assert!(day.has_project("beta"));
+
+        day.content.push("b1".to_string());
+        assert_eq!(day.project_lines("beta"), vec!["b1"]);
+    }
+
+    #[test]
+    fn timeline_runs_oldest_first() {
+        // Days are stored newest first
+        let days = Days {
+            days: vec![
+                Day::with_lines((2024, 1, 3), &["project: alpha", "third"]),
+                Day::with_lines((2024, 1, 2), &["project: beta", "skipped"]),
+                Day::with_lines((2024, 1, 1), &["project: alpha", "first"]),
+            ],
+        };
+        let timeline = days.project_timeline("alpha");
+        let dates: Vec<String> = timeline.iter().map(|(date, _)| date.to_string()).collect();
+        assert_eq!(dates, vec!["2024-01-01", "2024-01-03"]);
+        assert_eq!(timeline[0].1, vec!["first"]);
+        assert_eq!(timeline[1].1, vec!["third"]);
+    }
+}
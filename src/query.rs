@@ -0,0 +1,548 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::collections::BTreeSet;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::app::Day;
+use crate::daterange::{parse_phrase, period, DateRange};
+use crate::index::Index;
+use crate::metadata::{split_field_query, Comparison, FieldDef};
+use crate::people::MENTION_PREFIX;
+use crate::search::normalize;
+use crate::tag::TAG_PREFIX;
+
+// Filter expressions, e.g. `tag:perf after:2023-06-01 "load test" -draft weekday:fri hours>=6 in:Q3`
+// Every clause has to match. A leading `-` excludes instead
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    clauses: Vec<Clause>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Clause {
+    negated: bool,
+    term: Term,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Text(String),
+    Tag(String),
+    Project(String),
+    Person(String),
+    Range(DateRange),
+    Weekday(Weekday),
+    Field(String, Comparison, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub message: String,
+    // Char offset into the filter the error points at
+    pub position: usize,
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (col {})", self.message, self.position + 1)
+    }
+}
+
+fn error(message: impl Into<String>, position: usize) -> QueryError {
+    QueryError {
+        message: message.into(),
+        position,
+    }
+}
+
+struct Token {
+    text: String,
+    start: usize,
+    // Whether the token opened with a quote, in which case it's always plain text
+    quoted: bool,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut current: Option<Token> = None;
+    let mut quote_start: Option<usize> = None;
+    for (position, c) in input.chars().enumerate() {
+        if c == '"' {
+            if current.is_none() {
+                current = Some(Token {
+                    text: String::new(),
+                    start: position,
+                    quoted: true,
+                });
+            }
+            quote_start = match quote_start {
+                Some(_) => None,
+                None => Some(position),
+            };
+        } else if c.is_whitespace() && quote_start.is_none() {
+            if let Some(token) = current.take() {
+                tokens.push(token);
+            }
+        } else {
+            current
+                .get_or_insert(Token {
+                    text: String::new(),
+                    start: position,
+                    quoted: false,
+                })
+                .text
+                .push(c);
+        }
+    }
+    if let Some(position) = quote_start {
+        return Err(error("unclosed quote", position));
+    }
+    if let Some(token) = current.take() {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn parse_date(value: &str, position: usize) -> Result<NaiveDate, QueryError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%d/%m/%Y"))
+        .map_err(|_| error(format!("\"{}\" is not a yyyy-mm-dd date", value), position))
+}
+
+fn parse_term(token: &Token, fields: &[FieldDef], today: NaiveDate) -> Result<Term, QueryError> {
+    let text = token.text.as_str();
+    if token.quoted {
+        return Ok(Term::Text(text.to_string()));
+    }
+    if let Some(tag) = text.strip_prefix(TAG_PREFIX).filter(|t| !t.is_empty()) {
+        return Ok(Term::Tag(tag.to_string()));
+    }
+    if let Some(name) = text.strip_prefix(MENTION_PREFIX).filter(|n| !n.is_empty()) {
+        return Ok(Term::Person(name.to_string()));
+    }
+    let (name, comparison, value) = match split_field_query(text) {
+        Some(query) => query,
+        None => return Ok(Term::Text(text.to_string())),
+    };
+    let value_position = token.start + text.chars().count() - value.chars().count();
+    if value.is_empty() {
+        return Err(error(
+            format!("missing value for \"{}\"", name),
+            value_position,
+        ));
+    }
+    let key = name.to_lowercase();
+    if comparison == Comparison::Eq {
+        let term = match key.as_str() {
+            "tag" => Some(Term::Tag(value.to_string())),
+            "project" => Some(Term::Project(value.to_string())),
+            "person" => Some(Term::Person(value.to_string())),
+            "after" => Some(Term::Range(DateRange {
+                start: Some(parse_date(value, value_position)?),
+                end: None,
+            })),
+            "before" => Some(Term::Range(DateRange {
+                start: None,
+                end: Some(parse_date(value, value_position)?),
+            })),
+            "on" => {
+                let date = parse_date(value, value_position)?;
+                Some(Term::Range(DateRange {
+                    start: Some(date),
+                    end: Some(date),
+                }))
+            }
+            "in" => Some(Term::Range(period(value, today).ok_or_else(|| {
+                error(
+                    format!("\"{}\" is not a month, year, quarter or half", value),
+                    value_position,
+                )
+            })?)),
+            "weekday" => Some(Term::Weekday(value.parse().map_err(|_| {
+                error(format!("\"{}\" is not a weekday", value), value_position)
+            })?)),
+            _ => None,
+        };
+        if let Some(term) = term {
+            return Ok(term);
+        }
+    }
+    if fields.iter().any(|f| f.name.eq_ignore_ascii_case(name)) {
+        Ok(Term::Field(name.to_string(), comparison, value.to_string()))
+    } else {
+        Err(error(format!("unknown key \"{}\"", name), token.start))
+    }
+}
+
+// Date phrases like `last week` span several tokens, so they're read before the single token terms
+fn parse_range(tokens: &[Token], today: NaiveDate) -> Option<(DateRange, usize)> {
+    let words: Vec<&str> = tokens
+        .iter()
+        .take_while(|token| !token.quoted)
+        .map(|token| token.text.as_str())
+        .collect();
+    parse_phrase(&words, today)
+}
+
+// Relative dates such as `this month` are resolved against `today`
+pub fn parse(input: &str, fields: &[FieldDef], today: NaiveDate) -> Result<Query, QueryError> {
+    let mut clauses = Vec::new();
+    let mut tokens = tokenize(input)?;
+    let mut next = 0;
+    while next < tokens.len() {
+        let token = &mut tokens[next];
+        let negated = !token.quoted && token.text.starts_with('-');
+        if negated {
+            token.text.remove(0);
+            token.start += 1;
+            if token.text.is_empty() {
+                return Err(error("nothing to exclude after \"-\"", token.start - 1));
+            }
+        }
+        let term = match parse_range(&tokens[next..], today) {
+            Some((range, used)) => {
+                next += used;
+                Term::Range(range)
+            }
+            None => {
+                next += 1;
+                parse_term(&tokens[next - 1], fields, today)?
+            }
+        };
+        clauses.push(Clause { negated, term });
+    }
+    Ok(Query { clauses })
+}
+
+impl Term {
+    fn matches(&self, day: &Day, index: Option<&Index>) -> bool {
+        match self {
+            Term::Text(text) => index
+                .and_then(|index| index.contains_text(day.date, &normalize(text)))
+                .unwrap_or_else(|| day.contains_text(text)),
+            Term::Tag(tag) => day.has_tag(tag),
+            Term::Project(project) => day.has_project(project),
+            Term::Person(name) => day.mentions_person(name),
+            Term::Range(range) => range.contains(day.date),
+            Term::Weekday(weekday) => day.date.weekday() == *weekday,
+            Term::Field(name, comparison, value) => day.meta_matches(name, *comparison, value),
+        }
+    }
+}
+
+impl Query {
+    pub fn matches(&self, day: &Day) -> bool {
+        self.matches_indexed(day, None)
+    }
+
+    // Lets text terms use the index's normalized copy of the day instead of normalizing it again
+    pub fn matches_indexed(&self, day: &Day, index: Option<&Index>) -> bool {
+        self.clauses
+            .iter()
+            .all(|clause| clause.term.matches(day, index) != clause.negated)
+    }
+
+    // Narrows the days worth running `matches` on. None means every day is a candidate
+    pub fn candidates(&self, index: &Index) -> Option<BTreeSet<NaiveDate>> {
+        let mut candidates: Option<BTreeSet<NaiveDate>> = None;
+        for clause in self.clauses.iter().filter(|clause| !clause.negated) {
+            let term_candidates = match &clause.term {
+                Term::Text(text) => index.candidates(text),
+                Term::Tag(tag) => index.candidates(tag),
+                Term::Person(name) => index.candidates(name),
+                _ => None,
+            };
+            if let Some(term_candidates) = term_candidates {
+                candidates = Some(match candidates {
+                    Some(candidates) => {
+                        candidates.intersection(&term_candidates).copied().collect()
+                    }
+                    None => term_candidates,
+                });
+            }
+        }
+        candidates
+    }
+
+    // The days every date term allows, ignoring exclusions
+    pub fn date_range(&self) -> DateRange {
+        self.clauses
+            .iter()
+            .filter(|clause| !clause.negated)
+            .filter_map(|clause| match &clause.term {
+                Term::Range(range) => Some(range),
+                _ => None,
+            })
+            .fold(DateRange::all(), |all, range| all.intersect(range))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    // Words worth highlighting in a day that matched
+    pub fn highlight_terms(&self) -> Vec<String> {
+        self.clauses
+            .iter()
+            .filter(|clause| !clause.negated)
+            .filter_map(|clause| match &clause.term {
+                Term::Text(text) => Some(text.clone()),
+                Term::Tag(tag) => Some(format!("{}{}", TAG_PREFIX, tag)),
+                Term::Person(name) => Some(format!("{}{}", MENTION_PREFIX, name)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Case-insensitive pattern for the text area's search highlighting. The text isn't
+    // normalised there, so each term is looked for both composed and decomposed
+    pub fn highlight_pattern(&self) -> Option<String> {
+        let mut terms: Vec<String> = Vec::new();
+        for term in self.highlight_terms() {
+            for form in [term.nfkc().collect::<String>(), term.nfkd().collect()] {
+                let form = regex::escape(&form);
+                if !terms.contains(&form) {
+                    terms.push(form);
+                }
+            }
+        }
+        if terms.is_empty() {
+            None
+        } else {
+            Some(format!("(?i){}", terms.join("|")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::FieldKind;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 13).unwrap()
+    }
+
+    fn fields() -> Vec<FieldDef> {
+        vec![FieldDef {
+            name: "hours".to_string(),
+            kind: FieldKind::Number,
+            options: Vec::new(),
+        }]
+    }
+
+    fn terms(input: &str) -> Vec<(bool, Term)> {
+        parse(input, &fields(), today())
+            .unwrap()
+            .clauses
+            .into_iter()
+            .map(|clause| (clause.negated, clause.term))
+            .collect()
+    }
+
+    fn fails_at(input: &str) -> usize {
+        parse(input, &fields(), today()).unwrap_err().position
+    }
+
+    #[test]
+    fn empty_input_matches_everything() {
+        for input in ["", "   "] {
+            let query = parse(input, &[], today()).unwrap();
+            assert!(query.is_empty());
+            assert!(query.matches(&Day::with_lines((2024, 1, 1), &[])));
+            assert_eq!(query.date_range(), DateRange::all());
+        }
+    }
+
+    #[test]
+    fn quotes_keep_phrases_together_and_as_text() {
+        assert_eq!(
+            terms(r#""load test" "tag:perf" "-draft""#),
+            vec![
+                (false, Term::Text("load test".to_string())),
+                (false, Term::Text("tag:perf".to_string())),
+                (false, Term::Text("-draft".to_string())),
+            ]
+        );
+        assert_eq!(
+            terms(r#"a"b c"d"#),
+            vec![(false, Term::Text("ab cd".to_string()))]
+        );
+        assert_eq!(terms(r#""""#), vec![(false, Term::Text(String::new()))]);
+    }
+
+    #[test]
+    fn reads_keys_and_prefixes() {
+        assert_eq!(
+            terms("#perf tag:db @alice person:bob project:api weekday:fri hours>=6"),
+            vec![
+                (false, Term::Tag("perf".to_string())),
+                (false, Term::Tag("db".to_string())),
+                (false, Term::Person("alice".to_string())),
+                (false, Term::Person("bob".to_string())),
+                (false, Term::Project("api".to_string())),
+                (false, Term::Weekday(Weekday::Fri)),
+                (
+                    false,
+                    Term::Field("hours".to_string(), Comparison::Ge, "6".to_string())
+                ),
+            ]
+        );
+        // A prefix on its own is just text
+        assert_eq!(
+            terms("# @"),
+            vec![
+                (false, Term::Text("#".to_string())),
+                (false, Term::Text("@".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn minus_binds_to_the_term_after_it() {
+        assert_eq!(
+            terms("-draft -tag:wip -last week"),
+            vec![
+                (true, Term::Text("draft".to_string())),
+                (true, Term::Tag("wip".to_string())),
+                (
+                    true,
+                    Term::Range(DateRange {
+                        start: NaiveDate::from_ymd_opt(2024, 3, 4),
+                        end: NaiveDate::from_ymd_opt(2024, 3, 10),
+                    })
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn date_keys_become_ranges() {
+        let date = NaiveDate::from_ymd_opt(2023, 6, 1);
+        assert_eq!(
+            terms("after:2023-06-01 before:01/06/2023 on:2023-06-01"),
+            vec![
+                (
+                    false,
+                    Term::Range(DateRange {
+                        start: date,
+                        end: None
+                    })
+                ),
+                (
+                    false,
+                    Term::Range(DateRange {
+                        start: None,
+                        end: date
+                    })
+                ),
+                (
+                    false,
+                    Term::Range(DateRange {
+                        start: date,
+                        end: date
+                    })
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn words_that_look_like_dates_are_text_unless_they_are_in_a_date() {
+        let q3 = Term::Range(DateRange {
+            start: NaiveDate::from_ymd_opt(2024, 7, 1),
+            end: NaiveDate::from_ymd_opt(2024, 9, 30),
+        });
+        assert_eq!(
+            terms("Q3 planning since then 2023"),
+            vec![
+                (false, q3.clone()),
+                (false, Term::Text("planning".to_string())),
+                (false, Term::Text("since".to_string())),
+                (false, Term::Text("then".to_string())),
+                (false, Term::Text("2023".to_string())),
+            ]
+        );
+        assert_eq!(
+            terms("-h2"),
+            vec![(
+                true,
+                Term::Range(DateRange {
+                    start: NaiveDate::from_ymd_opt(2024, 7, 1),
+                    end: NaiveDate::from_ymd_opt(2024, 12, 31),
+                })
+            )]
+        );
+        assert_eq!(
+            terms(r#""Q3""#),
+            vec![(false, Term::Text("Q3".to_string()))]
+        );
+        assert_eq!(terms("in:Q3"), vec![(false, q3)]);
+        assert_eq!(terms("Q3 2024").len(), 1);
+        assert_eq!(fails_at("in:Q5"), 3);
+    }
+
+    #[test]
+    fn points_errors_at_the_problem() {
+        assert_eq!(fails_at(r#"ok "never closed"#), 3);
+        assert_eq!(fails_at("ok -"), 3);
+        assert_eq!(fails_at("after:yesterdayish"), 6);
+        assert_eq!(fails_at("tag:"), 4);
+        assert_eq!(fails_at("weekday:someday"), 8);
+        assert_eq!(fails_at("ok colour:red"), 3);
+        // Fields that aren't in the config are unknown keys too
+        assert!(parse("hours>=6", &[], today()).is_err());
+    }
+
+    #[test]
+    fn every_clause_has_to_match() {
+        let query = parse("#perf -draft after:2024-01-02", &[], today()).unwrap();
+        assert!(query.matches(&Day::with_lines((2024, 1, 3), &["#perf tuning"])));
+        assert!(!query.matches(&Day::with_lines((2024, 1, 3), &["#perf tuning", "draft"])));
+        assert!(!query.matches(&Day::with_lines((2024, 1, 1), &["#perf tuning"])));
+        assert!(!query.matches(&Day::with_lines((2024, 1, 3), &["tuning"])));
+    }
+
+    #[test]
+    fn date_range_intersects_and_ignores_exclusions() {
+        let query = parse(
+            "after:2024-01-01 before:2024-02-01 -on:2024-01-15",
+            &[],
+            today(),
+        )
+        .unwrap();
+        assert_eq!(
+            query.date_range(),
+            DateRange {
+                start: NaiveDate::from_ymd_opt(2024, 1, 1),
+                end: NaiveDate::from_ymd_opt(2024, 2, 1),
+            }
+        );
+    }
+
+    #[test]
+    fn highlights_what_was_searched_for() {
+        let query = parse("a.b #perf @al -hidden after:2024-01-01", &[], today()).unwrap();
+        assert_eq!(query.highlight_terms(), vec!["a.b", "#perf", "@al"]);
+        assert_eq!(
+            query.highlight_pattern().as_deref(),
+            Some(r"(?i)a\.b|\#perf|@al")
+        );
+        assert_eq!(parse("-x", &[], today()).unwrap().highlight_pattern(), None);
+    }
+
+    #[test]
+    fn highlights_composed_and_decomposed_forms() {
+        let query = parse("cafe\u{301}", &[], today()).unwrap();
+        assert_eq!(
+            query.highlight_pattern().as_deref(),
+            Some("(?i)caf\u{e9}|cafe\u{301}")
+        );
+        assert_eq!(
+            parse("plain", &[], today())
+                .unwrap()
+                .highlight_pattern()
+                .as_deref(),
+            Some("(?i)plain")
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use chrono::NaiveDate;
+use regex::Regex;
+
+use crate::app::Days;
+use crate::daterange::DateRange;
+
+// What's being typed into the find and replace popup
+pub struct ReplaceBuffer {
+    pub find: String,
+    pub with: String,
+    pub dates: String,
+    pub regex: bool,
+    // Only touch the days the current filter shows
+    pub filtered_only: bool,
+    pub currently_selected: u8,
+    pub error: Option<String>,
+}
+
+impl ReplaceBuffer {
+    pub fn new() -> Self {
+        Self {
+            find: String::new(),
+            with: String::new(),
+            dates: String::new(),
+            regex: false,
+            filtered_only: false,
+            currently_selected: 0,
+            error: None,
+        }
+    }
+
+    fn selected(&mut self) -> &mut String {
+        match self.currently_selected {
+            0 => &mut self.find,
+            1 => &mut self.with,
+            _ => &mut self.dates,
+        }
+    }
+
+    pub fn push(&mut self, char: char) {
+        self.selected().push(char);
+        self.error = None;
+    }
+
+    pub fn pop(&mut self) {
+        self.selected().pop();
+        self.error = None;
+    }
+}
+
+impl Default for ReplaceBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub enum Pattern {
+    Plain(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn new(find: &str, regex: bool) -> Result<Self, String> {
+        if find.is_empty() {
+            return Err("nothing to find".to_string());
+        }
+        if regex {
+            Regex::new(find)
+                .map(Pattern::Regex)
+                .map_err(|e| e.to_string().lines().last().unwrap_or_default().to_string())
+        } else {
+            Ok(Pattern::Plain(find.to_string()))
+        }
+    }
+
+    // The line with every match replaced, or None if nothing matched. Regexes can use `$1` etc
+    pub fn replace_line(&self, line: &str, with: &str) -> Option<String> {
+        let replaced = match self {
+            Pattern::Plain(find) if line.contains(find.as_str()) => line.replace(find, with),
+            Pattern::Regex(regex) if regex.is_match(line) => {
+                regex.replace_all(line, with).into_owned()
+            }
+            _ => return None,
+        };
+        (replaced != line).then_some(replaced)
+    }
+}
+
+// One line that would change
+pub struct Change {
+    pub date: NaiveDate,
+    pub row: usize,
+    pub before: String,
+    pub after: String,
+}
+
+// A day as it was before and after a replace, so undoing can tell if it's been edited since
+pub struct Undo {
+    pub date: NaiveDate,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+// Every change the replacement would make to the days at `positions` within `range`
+pub fn plan(
+    days: &Days,
+    positions: impl Iterator<Item = usize>,
+    range: &DateRange,
+    pattern: &Pattern,
+    with: &str,
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for day in positions.filter_map(|position| days.days.get(position)) {
+        if !range.contains(day.date) {
+            continue;
+        }
+        for (row, line) in day.content.iter().enumerate() {
+            if let Some(after) = pattern.replace_line(line, with) {
+                changes.push(Change {
+                    date: day.date,
+                    row,
+                    before: line.clone(),
+                    after,
+                });
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Day;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn rejects_empty_and_broken_patterns() {
+        assert_eq!(
+            Pattern::new("", false).err().as_deref(),
+            Some("nothing to find")
+        );
+        assert_eq!(
+            Pattern::new("", true).err().as_deref(),
+            Some("nothing to find")
+        );
+        let error = Pattern::new("(unclosed", true).err().unwrap();
+        assert!(error.contains("unclosed"), "{}", error);
+    }
+
+    #[test]
+    fn plain_text_is_matched_literally() {
+        let pattern = Pattern::new("a.b", false).unwrap();
+        assert_eq!(
+            pattern.replace_line("a.b axb a.b", "c").as_deref(),
+            Some("c axb c")
+        );
+        assert_eq!(pattern.replace_line("axb", "c"), None);
+        assert_eq!(pattern.replace_line("", "c"), None);
+    }
+
+    #[test]
+    fn regexes_can_use_groups() {
+        let pattern = Pattern::new(r"(\w+)@(\w+)", true).unwrap();
+        assert_eq!(
+            pattern.replace_line("mail bob@work", "$2/$1").as_deref(),
+            Some("mail work/bob")
+        );
+        assert_eq!(pattern.replace_line("nobody", "$1"), None);
+    }
+
+    #[test]
+    fn replacing_with_the_same_text_is_no_change() {
+        let pattern = Pattern::new("same", false).unwrap();
+        assert_eq!(pattern.replace_line("the same", "same"), None);
+    }
+
+    #[test]
+    fn plans_only_the_given_days_in_range() {
+        let days = Days {
+            days: vec![
+                Day::with_lines((2024, 1, 3), &["todo", "done"]),
+                Day::with_lines((2024, 1, 2), &["todo"]),
+                Day::with_lines((2024, 1, 1), &["todo todo"]),
+            ],
+        };
+        let pattern = Pattern::new("todo", false).unwrap();
+        let range = DateRange {
+            start: Some(date(2)),
+            end: None,
+        };
+        let changes = plan(&days, [0, 2].into_iter(), &range, &pattern, "done");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].date, date(3));
+        assert_eq!(changes[0].row, 0);
+        assert_eq!(changes[0].before, "todo");
+        assert_eq!(changes[0].after, "done");
+
+        let changes = plan(&days, 0..3, &DateRange::all(), &pattern, "x");
+        let rows: Vec<(NaiveDate, usize)> = changes.iter().map(|c| (c.date, c.row)).collect();
+        assert_eq!(rows, vec![(date(3), 0), (date(2), 0), (date(1), 0)]);
+        assert_eq!(changes[2].after, "x x");
+    }
+}
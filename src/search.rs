@@ -0,0 +1,171 @@
+use chrono::NaiveDate;
+use serde_derive::{Deserialize, Serialize};
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::app::Day;
+use crate::query::Query;
+
+// Compatibility composed and lowercased so "Café", "CAFÉ" and "cafe\u{301}" all compare equal
+pub fn normalize(text: &str) -> String {
+    text.nfkc().collect::<String>().to_lowercase()
+}
+
+impl Day {
+    pub fn contains_text(&self, term: &str) -> bool {
+        let term = normalize(term);
+        self.date_pretty().contains(&term)
+            || self
+                .content
+                .iter()
+                .any(|line| normalize(line).contains(&term))
+    }
+}
+
+// A filter kept in the config file so it can be applied again in one go
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub filter: String,
+}
+
+// A line that matched the filter, with the byte ranges to highlight
+pub struct SearchHit {
+    pub date: NaiveDate,
+    pub row: usize,
+    pub ranges: Vec<(usize, usize)>,
+}
+
+// The line normalized a char (with the combining marks after it) at a time, along with
+// the byte range in the line each normalized byte came from
+fn normalize_mapped(line: &str) -> (String, Vec<(usize, usize)>) {
+    let mut normalized = String::new();
+    let mut origins = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, mark)) = chars.peek() {
+            if canonical_combining_class(mark) == 0 {
+                break;
+            }
+            end = i + mark.len_utf8();
+            chars.next();
+        }
+        let cluster = normalize(&line[start..end]);
+        origins.extend(std::iter::repeat_n((start, end), cluster.len()));
+        normalized.push_str(&cluster);
+    }
+    (normalized, origins)
+}
+
+// Byte ranges of the line where any of the (normalized) terms match, sorted and merged
+fn highlight_ranges(line: &str, terms: &[String]) -> Vec<(usize, usize)> {
+    let (normalized, origins) = normalize_mapped(line);
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for term in terms.iter().filter(|term| !term.is_empty()) {
+        for (i, found) in normalized.match_indices(term.as_str()) {
+            ranges.push((origins[i].0, origins[i + found.len() - 1].1));
+        }
+    }
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+pub fn search_hits<'a>(days: impl Iterator<Item = &'a Day>, query: &Query) -> Vec<SearchHit> {
+    let terms: Vec<String> = query
+        .highlight_terms()
+        .iter()
+        .map(|t| normalize(t))
+        .collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let mut hits = Vec::new();
+    for day in days {
+        for (row, line) in day.content.iter().enumerate() {
+            let normalized = normalize(line);
+            if !terms.iter().any(|term| normalized.contains(term)) {
+                continue;
+            }
+            hits.push(SearchHit {
+                date: day.date,
+                row,
+                ranges: highlight_ranges(line, &terms),
+            });
+        }
+    }
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse;
+
+    #[test]
+    fn normalizes_case_and_composition() {
+        assert_eq!(normalize("Café"), "café");
+        assert_eq!(normalize("CAFÉ"), "café");
+        assert_eq!(normalize("cafe\u{301}"), "café");
+        // Compatibility forms too, like the "fi" ligature
+        assert_eq!(normalize("\u{fb01}le"), "file");
+    }
+
+    #[test]
+    fn days_contain_text_in_any_form() {
+        let day = Day::with_lines((2024, 1, 2), &["Lunch at the cafe\u{301}"]);
+        for term in ["café", "CAFÉ", "cafe\u{301}", "lunch"] {
+            assert!(day.contains_text(term), "{}", term);
+        }
+        assert!(!day.contains_text("cafes"));
+        // The date as it's shown counts as text
+        assert!(day.contains_text("02/01/2024"));
+    }
+
+    fn hits(content: &[&str], filter: &str) -> Vec<(usize, Vec<(usize, usize)>)> {
+        let day = Day::with_lines((2024, 1, 2), content);
+        let query = parse(filter, &[], day.date).unwrap();
+        search_hits(std::iter::once(&day), &query)
+            .into_iter()
+            .map(|hit| (hit.row, hit.ranges))
+            .collect()
+    }
+
+    #[test]
+    fn highlights_matches_in_the_original_line() {
+        let decomposed = "Lunch at the cafe\u{301}, CAFÉ after";
+        let start = decomposed.find("cafe").unwrap();
+        let upper = decomposed.find("CAFÉ").unwrap();
+        assert_eq!(
+            hits(&["nothing here", decomposed], "café"),
+            vec![(
+                1,
+                vec![
+                    (start, start + "cafe\u{301}".len()),
+                    (upper, upper + "CAFÉ".len())
+                ]
+            )]
+        );
+        // A ligature is highlighted whole
+        assert_eq!(hits(&["\u{fb01}le"], "fi"), vec![(0, vec![(0, 3)])]);
+    }
+
+    #[test]
+    fn merges_overlapping_matches() {
+        assert_eq!(hits(&["aaa"], "aa a"), vec![(0, vec![(0, 3)])]);
+        assert_eq!(hits(&["x #perf"], "#perf perf"), vec![(0, vec![(2, 7)])]);
+    }
+
+    #[test]
+    fn only_text_terms_find_lines() {
+        assert!(hits(&["after"], "after:2024-01-01").is_empty());
+        assert!(hits(&["draft"], "-draft").is_empty());
+    }
+}
@@ -0,0 +1,277 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::app::{App, CurrentScreen, Day};
+use crate::calendar::{first_of_month, shift_months};
+
+// How many tags and people the stats screen lists
+const TOP: usize = 8;
+
+// Everything the stats screen shows, worked out from the days the filter lets through
+#[derive(Default)]
+pub struct Stats {
+    pub days: usize,
+    pub words: usize,
+    // Every month from the first day logged to the last, including empty ones
+    pub days_per_month: Vec<(NaiveDate, u64)>,
+    // Oldest first
+    pub words_per_day: Vec<(NaiveDate, u64)>,
+    // Monday of every week from the first day logged to the last
+    pub words_per_week: Vec<(NaiveDate, u64)>,
+    // Weekdays in a row with something logged. Weekends neither count nor break a streak
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub top_tags: Vec<(String, usize)>,
+    pub top_people: Vec<(String, usize)>,
+    // Days logged on each day of the week, Monday first
+    pub weekdays: [u64; 7],
+}
+
+impl Stats {
+    pub fn new<'a>(days: impl Iterator<Item = &'a Day>, today: NaiveDate) -> Self {
+        // The filter can rank days rather than sort them, so put them back in order
+        let mut days: Vec<&Day> = days.filter(|day| day.word_count() > 0).collect();
+        days.sort_by_key(|day| day.date);
+        let (first, last) = match (days.first(), days.last()) {
+            (Some(first), Some(last)) => (first.date, last.date),
+            _ => return Self::default(),
+        };
+
+        let mut days_per_month = Vec::new();
+        let mut month = first_of_month(first);
+        while month <= last {
+            let next = shift_months(month, 1);
+            let count = days
+                .iter()
+                .filter(|day| day.date >= month && day.date < next)
+                .count();
+            days_per_month.push((month, count as u64));
+            month = next;
+        }
+
+        let mut words_per_week = Vec::new();
+        let mut week = monday_of(first);
+        while week <= last {
+            let next = week + Duration::days(7);
+            let words = days
+                .iter()
+                .filter(|day| day.date >= week && day.date < next)
+                .map(|day| day.word_count())
+                .sum::<usize>();
+            words_per_week.push((week, words as u64));
+            week = next;
+        }
+
+        let mut weekdays = [0; 7];
+        for day in days.iter() {
+            weekdays[day.date.weekday().num_days_from_monday() as usize] += 1;
+        }
+
+        let logged: BTreeSet<NaiveDate> = days.iter().map(|day| day.date).collect();
+        let (current_streak, longest_streak) = streaks(&logged, today);
+
+        Self {
+            days: days.len(),
+            words: days.iter().map(|day| day.word_count()).sum(),
+            days_per_month,
+            words_per_day: days
+                .iter()
+                .map(|day| (day.date, day.word_count() as u64))
+                .collect(),
+            words_per_week,
+            current_streak,
+            longest_streak,
+            top_tags: top(days.iter().flat_map(|day| day.tags())),
+            top_people: top(days.iter().flat_map(|day| day.mentions())),
+            weekdays,
+        }
+    }
+
+    // Weekdays from busiest to quietest, leaving out ones with nothing logged
+    pub fn busiest_weekdays(&self) -> Vec<(Weekday, u64)> {
+        let mut weekday = Weekday::Mon;
+        let mut busiest = Vec::new();
+        for count in self.weekdays {
+            if count > 0 {
+                busiest.push((weekday, count));
+            }
+            weekday = weekday.succ();
+        }
+        busiest.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        busiest
+    }
+}
+
+fn monday_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn is_weekday(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn previous_weekday(date: NaiveDate) -> NaiveDate {
+    let mut date = date - Duration::days(1);
+    while !is_weekday(date) {
+        date -= Duration::days(1);
+    }
+    date
+}
+
+// The current streak is still going if today isn't logged yet but the weekday before it was
+fn streaks(logged: &BTreeSet<NaiveDate>, today: NaiveDate) -> (usize, usize) {
+    let weekdays: Vec<NaiveDate> = logged.iter().copied().filter(|d| is_weekday(*d)).collect();
+    let mut longest = 0;
+    let mut run = 0;
+    for (i, date) in weekdays.iter().enumerate() {
+        run = match i.checked_sub(1).map(|i| weekdays[i]) {
+            Some(before) if before == previous_weekday(*date) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+    }
+
+    let mut day = if is_weekday(today) && logged.contains(&today) {
+        today
+    } else {
+        previous_weekday(today)
+    };
+    let mut current = 0;
+    while logged.contains(&day) {
+        current += 1;
+        day = previous_weekday(day);
+    }
+    (current, longest)
+}
+
+// Most used first, counted without regard to case and shown the way they were first written
+fn top<'a>(names: impl Iterator<Item = &'a str>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+    for name in names {
+        counts
+            .entry(name.to_lowercase())
+            .or_insert_with(|| (name.to_string(), 0))
+            .1 += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_values().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(TOP);
+    counts
+}
+
+impl App<'_> {
+    pub fn show_stats(&mut self) {
+        self.stats = Stats::new(self.filtered_days(), self.now());
+        self.current_screen = CurrentScreen::Stats;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn logged(dates: &[(i32, u32, u32)]) -> BTreeSet<NaiveDate> {
+        dates.iter().map(|(y, m, d)| date(*y, *m, *d)).collect()
+    }
+
+    #[test]
+    fn counts_the_days_with_something_written() {
+        let days = [
+            Day::with_lines((2023, 11, 1), &["four five six @ann @Bob #home"]),
+            Day::with_lines((2023, 9, 30), &[""]),
+            Day::with_lines((2023, 10, 2), &["one two #work @Ann"]),
+            Day::with_lines((2023, 10, 6), &["three #Work"]),
+        ];
+        let stats = Stats::new(days.iter(), date(2023, 11, 1));
+        assert_eq!(stats.days, 3);
+        assert_eq!(stats.words, 12);
+        assert_eq!(
+            stats.days_per_month,
+            [(date(2023, 10, 1), 2), (date(2023, 11, 1), 1)]
+        );
+        assert_eq!(
+            stats.words_per_day,
+            [
+                (date(2023, 10, 2), 4),
+                (date(2023, 10, 6), 2),
+                (date(2023, 11, 1), 6)
+            ]
+        );
+        assert_eq!(
+            stats.words_per_week,
+            [
+                (date(2023, 10, 2), 6),
+                (date(2023, 10, 9), 0),
+                (date(2023, 10, 16), 0),
+                (date(2023, 10, 23), 0),
+                (date(2023, 10, 30), 6)
+            ]
+        );
+        assert_eq!(stats.weekdays, [1, 0, 1, 0, 1, 0, 0]);
+        assert_eq!(
+            stats.busiest_weekdays(),
+            [(Weekday::Mon, 1), (Weekday::Wed, 1), (Weekday::Fri, 1)]
+        );
+        assert_eq!(
+            stats.top_tags,
+            [("work".to_string(), 2), ("home".to_string(), 1)]
+        );
+        assert_eq!(
+            stats.top_people,
+            [("Ann".to_string(), 2), ("Bob".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn nothing_written_is_empty() {
+        let days = [Day::with_lines((2023, 10, 2), &["", "  "])];
+        let stats = Stats::new(days.iter(), date(2023, 10, 2));
+        assert_eq!(stats.days, 0);
+        assert!(stats.days_per_month.is_empty());
+        assert!(stats.words_per_week.is_empty());
+        assert!(stats.busiest_weekdays().is_empty());
+    }
+
+    #[test]
+    fn streaks_skip_weekends() {
+        // Mon 2nd, then Thu 5th to Tue 10th with the weekend in between
+        let days = logged(&[
+            (2023, 10, 2),
+            (2023, 10, 5),
+            (2023, 10, 6),
+            (2023, 10, 7),
+            (2023, 10, 9),
+            (2023, 10, 10),
+        ]);
+        assert_eq!(streaks(&days, date(2023, 10, 10)), (4, 4));
+        // Today not being written yet doesn't end it
+        assert_eq!(streaks(&days, date(2023, 10, 11)), (4, 4));
+        assert_eq!(streaks(&days, date(2023, 10, 12)), (0, 4));
+        // On a weekend it runs up to the Friday
+        assert_eq!(streaks(&days, date(2023, 10, 7)), (2, 4));
+        assert_eq!(streaks(&days, date(2023, 10, 14)), (0, 4));
+    }
+
+    #[test]
+    fn weekends_alone_make_no_streak() {
+        let days = logged(&[(2023, 10, 7), (2023, 10, 8)]);
+        assert_eq!(streaks(&days, date(2023, 10, 8)), (0, 0));
+        assert_eq!(streaks(&BTreeSet::new(), date(2023, 10, 8)), (0, 0));
+    }
+
+    #[test]
+    fn top_ignores_case_and_keeps_the_busiest() {
+        let names = ["b", "A", "a", "c", "B", "d", "e", "f", "g", "h", "i", "j"];
+        let top = top(names.into_iter());
+        assert_eq!(top.len(), TOP);
+        assert_eq!(top[0], ("A".to_string(), 2));
+        assert_eq!(top[1], ("b".to_string(), 2));
+        // Ties go alphabetically
+        assert_eq!(top[2], ("c".to_string(), 1));
+        assert_eq!(top[7], ("h".to_string(), 1));
+    }
+}
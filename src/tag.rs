@@ -0,0 +1,37 @@
+use crate::app::Day;
+
+pub const TAG_PREFIX: char = '#';
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '/'
+}
+
+// `#tag` at the start of a word. Markdown headings (`# Title`) have a space so never match
+pub fn tags(line: &str) -> Vec<&str> {
+    let mut tags = Vec::new();
+    let mut previous: Option<char> = None;
+    for (index, c) in line.char_indices() {
+        let at_word_start = previous.is_none_or(|p| p.is_whitespace() || p == '(');
+        if c == TAG_PREFIX && at_word_start {
+            let rest = &line[index + c.len_utf8()..];
+            let end = rest.find(|c| !is_tag_char(c)).unwrap_or(rest.len());
+            let tag = rest[..end].trim_end_matches(['-', '/']);
+            // `#123` is more likely an issue number than a tag
+            if tag.chars().any(|c| !c.is_ascii_digit()) {
+                tags.push(tag);
+            }
+        }
+        previous = Some(c);
+    }
+    tags
+}
+
+impl Day {
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.content.iter().flat_map(|line| tags(line))
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::NaiveDate;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::app::{Day, Days};
+
+// Templates live next to the config file:
+// templates/default.md for every day, templates/friday.md etc. to override a weekday
+const TEMPLATES_DIR: &str = "templates";
+const DEFAULT_TEMPLATE: &str = "default";
+
+const DATE_VARIABLE: &str = "{{date}}";
+const WEEKDAY_VARIABLE: &str = "{{weekday}}";
+const OPEN_ITEMS_VARIABLE: &str = "{{open_items}}";
+
+pub fn templates_dir() -> Option<PathBuf> {
+    let config_path = confy::get_configuration_file_path("englog", None).ok()?;
+    Some(config_path.parent()?.join(TEMPLATES_DIR))
+}
+
+fn read_template(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(format!("{}.md", name))).ok()
+}
+
+pub fn load_template(dir: &Path, date: NaiveDate) -> Option<String> {
+    let weekday = date.format("%A").to_string().to_lowercase();
+    read_template(dir, &weekday).or_else(|| read_template(dir, DEFAULT_TEMPLATE))
+}
+
+// Unchecked `- [ ]` items
+pub fn open_items(day: &Day) -> Vec<&str> {
+    day.content
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| line.starts_with("- [ ]") || line.starts_with("* [ ]"))
+        .collect()
+}
+
+pub fn render(template: &str, date: NaiveDate, previous: Option<&Day>) -> Vec<String> {
+    let items = previous.map(open_items).unwrap_or_default();
+    let mut lines = Vec::new();
+    for line in template.lines() {
+        let line = line
+            .replace(DATE_VARIABLE, &date.format("%d/%m/%Y").to_string())
+            .replace(WEEKDAY_VARIABLE, &date.format("%A").to_string());
+        let Some((prefix, suffix)) = line.split_once(OPEN_ITEMS_VARIABLE) else {
+            lines.push(line);
+            continue;
+        };
+        // Items go one per line where the variable was, with the text around it kept on the
+        // first and last and the indent carried down to the rest
+        if items.is_empty() {
+            let rest = format!("{}{}", prefix, suffix);
+            if !rest.trim().is_empty() {
+                lines.push(rest.trim_end().to_string());
+            }
+            continue;
+        }
+        let indent = &prefix[..prefix.len() - prefix.trim_start().len()];
+        for (i, item) in items.iter().enumerate() {
+            let start = if i == 0 { prefix } else { indent };
+            let end = if i + 1 == items.len() { suffix } else { "" };
+            lines.push(format!("{}{}{}", start, item, end));
+        }
+    }
+    lines
+}
+
+impl Days {
+    // The latest day before `date` that has anything written in it
+    pub fn previous_day(&self, date: NaiveDate) -> Option<&Day> {
+        self.iter()
+            .find(|d| d.date < date && d.content.iter().any(|l| !l.trim().is_empty()))
+    }
+
+    pub fn new_day_from_template(&self, date: NaiveDate) -> Day {
+        let mut day = Day::new(date);
+        if let Some(template) = templates_dir().and_then(|dir| load_template(&dir, date)) {
+            day.content = render(&template, date, self.previous_day(date));
+        }
+        day
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 8).unwrap()
+    }
+
+    fn previous(content: &[&str]) -> Day {
+        let mut day = Day::new(NaiveDate::from_ymd_opt(2024, 3, 7).unwrap());
+        day.content = content.iter().map(|line| line.to_string()).collect();
+        day
+    }
+
+    #[test]
+    fn fills_in_the_date_and_weekday() {
+        assert_eq!(
+            render("# {{weekday}} {{date}}", date(), None),
+            vec!["# Friday 08/03/2024"]
+        );
+    }
+
+    #[test]
+    fn keeps_text_around_open_items() {
+        let day = previous(&["- [ ] one", "- [x] done", "  - [ ] two"]);
+        assert_eq!(
+            render(
+                "Carry over: {{open_items}} (from yesterday)",
+                date(),
+                Some(&day)
+            ),
+            vec!["Carry over: - [ ] one", "- [ ] two (from yesterday)"]
+        );
+        assert_eq!(
+            render("  {{open_items}}", date(), Some(&day)),
+            vec!["  - [ ] one", "  - [ ] two"]
+        );
+    }
+
+    #[test]
+    fn drops_a_bare_variable_with_nothing_open() {
+        let day = previous(&["- [x] done"]);
+        assert_eq!(
+            render("{{open_items}}\nend", date(), Some(&day)),
+            vec!["end"]
+        );
+        assert_eq!(
+            render("Carry over: {{open_items}}", date(), None),
+            vec!["Carry over:"]
+        );
+    }
+}
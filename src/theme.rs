@@ -0,0 +1,327 @@
+use ratatui::style::{Color, Style, Stylize};
+use serde_derive::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+// Custom themes live next to the config file as themes/<name>.toml. Any colour left out
+// comes from the built-in theme named by `extends`, or the dark one
+const THEMES_DIR: &str = "themes";
+const EXTENDS_KEY: &str = "extends";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Theme {
+    #[serde(with = "color")]
+    pub text: Color,
+    #[serde(with = "color")]
+    pub muted: Color,
+    // Dates and other things worth picking out of a list
+    #[serde(with = "color")]
+    pub accent: Color,
+    #[serde(with = "color")]
+    pub heading: Color,
+    #[serde(with = "color")]
+    pub link: Color,
+    #[serde(with = "color")]
+    pub selected_fg: Color,
+    #[serde(with = "color")]
+    pub selected_bg: Color,
+    // Filter matches and the active field or item in popups
+    #[serde(with = "color")]
+    pub match_fg: Color,
+    #[serde(with = "color")]
+    pub match_bg: Color,
+    #[serde(with = "color")]
+    pub popup_fg: Color,
+    #[serde(with = "color")]
+    pub popup_bg: Color,
+    #[serde(with = "color")]
+    pub button_fg: Color,
+    #[serde(with = "color")]
+    pub button_bg: Color,
+    // Errors, removed lines and the delete popup
+    #[serde(with = "color")]
+    pub danger: Color,
+    // Added lines and the info popup
+    #[serde(with = "color")]
+    pub success: Color,
+    // Calendar and heatmap shading, from the quietest days to the busiest
+    #[serde(with = "colors")]
+    pub activity: [Color; 4],
+    // One of the syntax themes bundled with the binary, for code blocks
+    pub code_theme: String,
+    // Set by NO_COLOR: everything is drawn in the terminal's own colours, using
+    // reversed and bold text where colour would otherwise be the only difference
+    #[serde(skip)]
+    pub plain: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            text: Color::White,
+            muted: Color::DarkGray,
+            accent: Color::Yellow,
+            heading: Color::Cyan,
+            link: Color::Cyan,
+            selected_fg: Color::White,
+            selected_bg: Color::Blue,
+            match_fg: Color::Black,
+            match_bg: Color::Yellow,
+            popup_fg: Color::White,
+            popup_bg: Color::Blue,
+            button_fg: Color::Black,
+            button_bg: Color::White,
+            danger: Color::Red,
+            success: Color::Green,
+            activity: [
+                Color::Rgb(14, 68, 41),
+                Color::Rgb(0, 109, 50),
+                Color::Rgb(38, 166, 65),
+                Color::Rgb(57, 211, 83),
+            ],
+            code_theme: "base16-ocean.dark".to_string(),
+            plain: false,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            text: Color::Black,
+            muted: Color::DarkGray,
+            accent: Color::Blue,
+            heading: Color::Magenta,
+            link: Color::Blue,
+            selected_fg: Color::White,
+            selected_bg: Color::Blue,
+            match_fg: Color::Black,
+            match_bg: Color::LightYellow,
+            popup_fg: Color::Black,
+            popup_bg: Color::Gray,
+            button_fg: Color::White,
+            button_bg: Color::Black,
+            danger: Color::Red,
+            success: Color::Green,
+            activity: [
+                Color::Rgb(155, 233, 168),
+                Color::Rgb(64, 196, 99),
+                Color::Rgb(48, 161, 78),
+                Color::Rgb(33, 110, 57),
+            ],
+            code_theme: "InspiredGitHub".to_string(),
+            plain: false,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            text: Color::White,
+            muted: Color::Gray,
+            accent: Color::LightYellow,
+            heading: Color::LightCyan,
+            link: Color::LightCyan,
+            selected_fg: Color::Black,
+            selected_bg: Color::White,
+            match_fg: Color::Black,
+            match_bg: Color::LightYellow,
+            popup_fg: Color::White,
+            popup_bg: Color::Black,
+            button_fg: Color::Black,
+            button_bg: Color::White,
+            danger: Color::LightRed,
+            success: Color::LightGreen,
+            activity: [Color::Blue, Color::Cyan, Color::Green, Color::LightGreen],
+            code_theme: "base16-eighties.dark".to_string(),
+            plain: false,
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    // A theme file in the config directory wins over a built-in theme of the same name.
+    // Anything unusable falls back to the dark theme rather than stopping the app starting
+    pub fn load(name: &str) -> Self {
+        let mut theme = themes_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(format!("{}.toml", name))).ok())
+            .and_then(|text| Self::from_toml(&text))
+            .or_else(|| Self::builtin(name))
+            .unwrap_or_default();
+        theme.plain = no_color();
+        theme
+    }
+
+    pub fn from_toml(text: &str) -> Option<Self> {
+        let mut overrides: toml::value::Table = toml::from_str(text).ok()?;
+        let base = overrides
+            .remove(EXTENDS_KEY)
+            .and_then(|name| name.as_str().and_then(Self::builtin))
+            .unwrap_or_default();
+        let mut merged = match toml::Value::try_from(base).ok()? {
+            toml::Value::Table(table) => table,
+            _ => return None,
+        };
+        merged.extend(overrides);
+        toml::Value::Table(merged).try_into().ok()
+    }
+
+    fn fg(&self, color: Color) -> Style {
+        if self.plain {
+            Style::default()
+        } else {
+            Style::default().fg(color)
+        }
+    }
+
+    fn pair(&self, fg: Color, bg: Color) -> Style {
+        if self.plain {
+            Style::default().reversed()
+        } else {
+            Style::default().fg(fg).bg(bg)
+        }
+    }
+
+    pub fn text(&self) -> Style {
+        self.fg(self.text)
+    }
+
+    pub fn muted(&self) -> Style {
+        if self.plain {
+            Style::default().dim()
+        } else {
+            self.fg(self.muted)
+        }
+    }
+
+    pub fn accent(&self) -> Style {
+        self.fg(self.accent).bold()
+    }
+
+    pub fn heading(&self) -> Style {
+        self.fg(self.heading).bold()
+    }
+
+    // List bullets and numbers in read mode
+    pub fn marker(&self) -> Style {
+        self.fg(self.heading)
+    }
+
+    // Inline code and code blocks in languages that aren't highlighted
+    pub fn code(&self) -> Style {
+        self.fg(self.accent)
+    }
+
+    pub fn link(&self) -> Style {
+        self.fg(self.link).underlined()
+    }
+
+    pub fn danger(&self) -> Style {
+        self.fg(self.danger)
+    }
+
+    pub fn success(&self) -> Style {
+        self.fg(self.success)
+    }
+
+    pub fn selected(&self) -> Style {
+        self.pair(self.selected_fg, self.selected_bg).bold()
+    }
+
+    pub fn matched(&self) -> Style {
+        self.pair(self.match_fg, self.match_bg)
+    }
+
+    pub fn button(&self) -> Style {
+        self.pair(self.button_fg, self.button_bg).bold()
+    }
+
+    pub fn popup(&self) -> Style {
+        self.popup_with(self.popup_bg)
+    }
+
+    pub fn danger_popup(&self) -> Style {
+        self.popup_with(self.danger)
+    }
+
+    pub fn info_popup(&self) -> Style {
+        self.popup_with(self.success)
+    }
+
+    fn popup_with(&self, bg: Color) -> Style {
+        if self.plain {
+            Style::default()
+        } else {
+            Style::default().fg(self.popup_fg).bg(bg)
+        }
+    }
+
+    // Background for an activity level from `calendar::activity_level`. Nothing written is unshaded
+    pub fn activity(&self, level: u8) -> Style {
+        match level {
+            0 => self.text(),
+            _ if self.plain => Style::default(),
+            _ => self
+                .text()
+                .bg(self.activity[(level as usize - 1).min(self.activity.len() - 1)]),
+        }
+    }
+}
+
+pub fn themes_dir() -> Option<PathBuf> {
+    let config_path = confy::get_configuration_file_path("englog", None).ok()?;
+    Some(config_path.parent()?.join(THEMES_DIR))
+}
+
+// https://no-color.org: any non-empty value turns colour off
+pub fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+// Colours are written the way people write them, e.g. "blue", "light-red", "#1e1e2e" or "236"
+mod color {
+    use ratatui::style::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&color.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse()
+            .map_err(|_| D::Error::custom(format!("unknown colour \"{}\"", text)))
+    }
+}
+
+mod colors {
+    use ratatui::style::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(colors: &[Color; 4], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(colors.iter().map(|color| color.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[Color; 4], D::Error> {
+        let texts = Vec::<String>::deserialize(deserializer)?;
+        let colors = texts
+            .iter()
+            .map(|text| {
+                text.parse()
+                    .map_err(|_| D::Error::custom(format!("unknown colour \"{}\"", text)))
+            })
+            .collect::<Result<Vec<Color>, _>>()?;
+        colors
+            .try_into()
+            .map_err(|_| D::Error::custom("activity needs exactly four colours"))
+    }
+}
@@ -2,12 +2,14 @@ use anyhow::Result;
 use crossterm::{
     event::{
         DisableMouseCapture, EnableMouseCapture, Event as CrossTermEvent, KeyEvent, KeyEventKind,
+        MouseEvent,
     },
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use futures::FutureExt;
 use futures::StreamExt;
-use ratatui::backend::CrosstermBackend;
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::Rect;
 use std::io::{self, Stdout};
 use std::time::Duration;
 use tokio::{
@@ -18,8 +20,6 @@ use tokio_util::sync::CancellationToken;
 
 use crate::app::App;
 
-type CrosstermTerminal = ratatui::Terminal<CrosstermBackend<Stdout>>;
-
 #[derive(Clone, Copy, Debug)]
 pub enum Loading {
     Saving(bool),
@@ -33,18 +33,21 @@ pub enum Event {
     Loading(Loading),
     // True means switch to edit screen for current day
     LoadDays(bool),
+    Resize(u16, u16),
+    Mouse(MouseEvent),
 }
 
-pub struct Tui {
-    terminal: CrosstermTerminal,
+// Generic over the backend so tests can drive it with a TestBackend
+pub struct Tui<B: Backend = CrosstermBackend<Stdout>> {
+    terminal: ratatui::Terminal<B>,
     pub event_rx: UnboundedReceiver<Event>,
     pub event_tx: UnboundedSender<Event>,
     cancellation_token: CancellationToken,
     task: JoinHandle<()>,
 }
 
-impl Tui {
-    pub fn new(terminal: CrosstermTerminal) -> Self {
+impl<B: Backend> Tui<B> {
+    pub fn new(terminal: ratatui::Terminal<B>) -> Self {
         let (event_tx, event_rx) = mpsc::unbounded_channel();
         Self {
             terminal,
@@ -75,10 +78,17 @@ impl Tui {
                         break;
                     }
                     maybe_event = crossterm_event => {
-                        if let Some(Ok(CrossTermEvent::Key(key))) = maybe_event {
-                            if key.kind == KeyEventKind::Press {
+                        match maybe_event {
+                            Some(Ok(CrossTermEvent::Key(key))) if key.kind == KeyEventKind::Press => {
                                 _event_tx.send(Event::Key(key)).unwrap();
                             }
+                            Some(Ok(CrossTermEvent::Mouse(mouse))) => {
+                                _event_tx.send(Event::Mouse(mouse)).unwrap();
+                            }
+                            Some(Ok(CrossTermEvent::Resize(width, height))) => {
+                                _event_tx.send(Event::Resize(width, height)).unwrap();
+                            }
+                            _ => {}
                         }
                     },
                     _ = tick_delay => {
@@ -110,7 +120,19 @@ impl Tui {
     }
 
     pub async fn next(&mut self) -> Option<Event> {
-        self.event_rx.recv().await
+        let event = self.event_rx.recv().await;
+        if let Some(Event::Resize(width, height)) = event {
+            // Failing here only costs a messy frame, the next draw resizes anyway
+            let _ = self.resize(width, height);
+        }
+        event
+    }
+
+    // Starts again from a blank screen at the new size instead of diffing against the old layout
+    pub fn resize(&mut self, width: u16, height: u16) -> Result<()> {
+        self.terminal.resize(Rect::new(0, 0, width, height))?;
+        self.terminal.clear()?;
+        Ok(())
     }
 
     pub fn stop(&self) -> Result<()> {
@@ -147,7 +169,15 @@ impl Tui {
     }
 }
 
-impl Tui {
+impl<B: Backend> Tui<B> {
+    pub fn backend(&self) -> &B {
+        self.terminal.backend()
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        self.terminal.backend_mut()
+    }
+
     pub fn draw(&mut self, app: &mut App) -> Result<()> {
         self.terminal.draw(|f| crate::ui::ui(f, app))?;
         Ok(())
@@ -1,16 +1,36 @@
+use chrono::{Datelike, NaiveDate};
+use crossterm::event::KeyCode;
 use ratatui::{
     prelude::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style, Stylize},
+    style::{Color, Modifier, Style, Stylize},
+    symbols,
     text::{Line, Span, Text},
     widgets::{
-        Block, Borders, Clear, List, ListItem, Padding, Paragraph, Scrollbar, ScrollbarState, Wrap,
+        Axis, BarChart, Block, Borders, Chart, Clear, Dataset, GraphType, List, ListItem,
+        ListState, Padding, Paragraph, Scrollbar, ScrollbarState, Sparkline, Wrap,
     },
     Frame,
 };
+use unicode_width::UnicodeWidthChar;
 
-use crate::app::{App, CurrentScreen, Popup};
+use crate::{
+    app::{App, CurrentScreen, Day, Info, Popup},
+    calendar::{activity_level, month_grid, year_grid, Activity},
+    daterange::DateRange,
+    fuzzy::matched_chars,
+    highlight::code_spans,
+    keymap::{show_sequence, Action, Context},
+    link::links,
+    markdown::render,
+    metadata::summarise,
+    mouse::{Button, ClickAreas},
+    search::SearchHit,
+    theme::Theme,
+    wrap::{columns, wrap_line},
+};
 
 pub fn ui(f: &mut Frame, app: &mut App) {
+    app.click_areas = ClickAreas::default();
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -29,30 +49,99 @@ pub fn ui(f: &mut Frame, app: &mut App) {
 
     render_footer(f, app, chunks[2]);
 
-    if let Some(popup) = &app.popup {
-        render_popup(f, app, popup);
+    if let Some(popup) = app.popup.clone() {
+        render_popup(f, app, &popup);
+    }
+}
+
+// Buttons sit on the popup's bottom border, right aligned, and are remembered for mouse clicks
+fn render_buttons(f: &mut Frame, app: &mut App, area: Rect, buttons: &[(&str, Button)]) {
+    let mut x = area.right().saturating_sub(2);
+    let y = area.bottom().saturating_sub(1);
+    for (label, button) in buttons.iter().rev() {
+        let text = format!("[ {} ]", label);
+        let width = text.chars().count() as u16;
+        if x < area.x + width + 1 {
+            break;
+        }
+        x -= width + 1;
+        let rect = Rect::new(x, y, width, 1);
+        f.render_widget(Paragraph::new(text).style(app.theme.button()), rect);
+        app.click_areas.buttons.push((rect, *button));
     }
 }
 
-fn render_popup(f: &mut Frame, app: &App, popup: &Popup) {
+fn render_popup(f: &mut Frame, app: &mut App, popup: &Popup) {
     match popup {
         Popup::ConfDeleteDay => {
             let delete_block = Block::default()
                 .title("Are you sure?")
-                .style(Style::default().bg(Color::Red).fg(Color::White))
+                .style(app.theme.danger_popup())
                 .borders(Borders::ALL);
-            let delete_text = Paragraph::new("y for yes\nAny other key to cancel".to_string())
+            let delete_text = Paragraph::new(confirm_prompt(app))
                 .wrap(Wrap::default())
                 .block(delete_block);
             let area = centered_rect(60, 15, f.size());
             f.render_widget(Clear, area);
             f.render_widget(delete_text, area);
+            render_buttons(
+                f,
+                app,
+                area,
+                &[
+                    ("Yes", Button::Action(Action::Confirm)),
+                    ("No", Button::Action(Action::Back)),
+                ],
+            );
+        }
+        Popup::ConfCreateDay(date) => {
+            let create_block = Block::default()
+                .title(format!(
+                    "Nothing written on {}",
+                    date.format("%-d %B, %C%y")
+                ))
+                .style(app.theme.info_popup())
+                .borders(Borders::ALL);
+            let create_text = Paragraph::new(format!("Start it now?\n{}", confirm_prompt(app)))
+                .wrap(Wrap::default())
+                .block(create_block);
+            let area = centered_rect(60, 15, f.size());
+            f.render_widget(Clear, area);
+            f.render_widget(create_text, area);
+            render_buttons(
+                f,
+                app,
+                area,
+                &[
+                    ("Yes", Button::Action(Action::Confirm)),
+                    ("No", Button::Action(Action::Back)),
+                ],
+            );
         }
-        Popup::Info(_) => {
+        Popup::Info(Info::KeymapErrors(errors)) => {
+            let mut lines = vec![
+                Line::from("Some key bindings in the config file couldn't be used, so the defaults are in use until they're fixed:"),
+                Line::default(),
+            ];
+            lines.extend(
+                errors
+                    .iter()
+                    .map(|error| Line::from(format!("- {}", error))),
+            );
+            let message_text = Paragraph::new(lines).wrap(Wrap::default()).block(
+                Block::default()
+                    .title("Key bindings")
+                    .borders(Borders::ALL)
+                    .padding(Padding::horizontal(1))
+                    .style(app.theme.danger_popup()),
+            );
+            let area = centered_rect(75, 60, f.size());
+            f.render_widget(Clear, area);
+            f.render_widget(message_text, area);
+            render_buttons(f, app, area, &[("Close", Button::Action(Action::Back))]);
+        }
+        Popup::Info(Info::About) => {
             let message = "Thanks for trying out the app\n
-There are a few known issues which i'm working on:
-1. Resizing may cause awkward rendering issues so please just quit and restart the app if this occurs
-2. Control scrolling may cause the selected day to go off screen.\n
 Any bugs found please just send requests and i'll see what I can do\n
 Useful shortcuts
 Please find all shortcuts at: https://github.com/rhysd/tui-textarea
@@ -72,13 +161,14 @@ Ctrl + P = Paste
             let message_block = Block::default()
                 .title("Info")
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Green).fg(Color::White));
+                .style(app.theme.info_popup());
             let message_text = Paragraph::new(message.to_string())
                 .wrap(Wrap::default())
                 .block(message_block);
             let area = centered_rect(75, 80, f.size());
             f.render_widget(Clear, area);
             f.render_widget(message_text, area);
+            render_buttons(f, app, area, &[("Close", Button::Action(Action::Back))]);
         }
         Popup::NewDay => {
             let area = centered_rect(60, 15, f.size());
@@ -96,7 +186,7 @@ Ctrl + P = Paste
             let mut month_block = Block::default().title("Month (1)").borders(Borders::ALL);
             let mut year_block = Block::default().title("Year (1970)").borders(Borders::ALL);
 
-            let active_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+            let active_style = app.theme.matched();
             match app.popup_buffer.currently_selected {
                 0 => day_block = day_block.style(active_style),
                 1 => month_block = month_block.style(active_style),
@@ -122,7 +212,7 @@ Ctrl + P = Paste
             let message_block = Block::default()
                 .title(title)
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Blue).fg(Color::White));
+                .style(app.theme.popup());
             let message_text = {
                 let word_limit = if *editing {
                     format!("{}{}", app.config_buffer.word_limit.clone(), "▌") // cry
@@ -137,6 +227,358 @@ Ctrl + P = Paste
             };
             f.render_widget(Clear, area);
             f.render_widget(message_text, area);
+            if *editing {
+                render_buttons(
+                    f,
+                    app,
+                    area,
+                    &[
+                        ("Save", Button::Key(KeyCode::Enter)),
+                        ("Cancel", Button::Key(KeyCode::Esc)),
+                    ],
+                );
+            } else {
+                render_buttons(
+                    f,
+                    app,
+                    area,
+                    &[
+                        ("Edit", Button::Action(Action::Edit)),
+                        ("Close", Button::Action(Action::Back)),
+                    ],
+                );
+            }
+        }
+        Popup::ProjectPicker(selected) => {
+            let area = centered_rect(40, 40, f.size());
+            let items: Vec<ListItem> = app
+                .config
+                .projects
+                .iter()
+                .enumerate()
+                .map(|(index, project)| {
+                    if index == *selected {
+                        ListItem::new(project.as_str()).style(app.theme.matched())
+                    } else {
+                        ListItem::new(project.as_str())
+                    }
+                })
+                .collect();
+            let list = List::new(items).block(
+                Block::default()
+                    .title("Assign project")
+                    .borders(Borders::ALL)
+                    .padding(Padding::horizontal(1))
+                    .style(app.theme.popup()),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(list, area);
+            render_buttons(
+                f,
+                app,
+                area,
+                &[
+                    ("Assign", Button::Action(Action::Select)),
+                    ("Cancel", Button::Action(Action::Back)),
+                ],
+            );
+        }
+        Popup::Metadata(selected, editing) => {
+            let area = centered_rect(70, 60, f.size());
+            let day = app.selected_day();
+            let title = match day {
+                Some(day) => format!("Metadata for {}", day.date_pretty()),
+                None => "Metadata".to_string(),
+            };
+            let mut lines = Vec::<Line>::new();
+            for (index, field) in app.config.fields.iter().enumerate() {
+                let value = if index == *selected && *editing {
+                    format!("{}▌", app.meta_buffer)
+                } else {
+                    day.and_then(|d| d.meta_value(&field.name))
+                        .map(|v| v.to_string())
+                        .unwrap_or_else(|| "-".to_string())
+                };
+                let text = format!("{}: {}", field.name, value);
+                if index == *selected {
+                    lines.push(Line::from(Span::styled(text, app.theme.matched())));
+                } else {
+                    lines.push(Line::from(text));
+                }
+            }
+            lines.push(Line::default());
+            let scope = if app.filter.is_some() {
+                "Filtered days"
+            } else {
+                "All days"
+            };
+            lines.push(Line::from(Span::styled(scope, Style::default().bold())));
+            for field in app.config.fields.iter() {
+                let summary = summarise(field, app.filtered_days());
+                lines.push(Line::from(format!("{}: {}", field.name, summary)));
+            }
+            let message_text = Paragraph::new(lines).wrap(Wrap::default()).block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .padding(Padding::horizontal(1))
+                    .style(app.theme.popup()),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(message_text, area);
+            let close = if *editing {
+                Button::Key(KeyCode::Esc)
+            } else {
+                Button::Action(Action::Back)
+            };
+            render_buttons(f, app, area, &[("Close", close)]);
+        }
+        Popup::SavedSearches(selected, naming) => {
+            let area = centered_rect(60, 50, f.size());
+            let mut lines = Vec::<Line>::new();
+            for (index, search) in app.config.searches.iter().enumerate() {
+                let count = match app.saved_search_count(search) {
+                    Some(count) => format!("{} days", count),
+                    None => "invalid".to_string(),
+                };
+                let shortcut = if index < 9 {
+                    format!("({}) ", index + 1)
+                } else {
+                    "    ".to_string()
+                };
+                let text = format!(
+                    "{}{} - {} [{}]",
+                    shortcut, search.name, search.filter, count
+                );
+                if index == *selected && !*naming {
+                    lines.push(Line::from(Span::styled(text, app.theme.matched())));
+                } else {
+                    lines.push(Line::from(text));
+                }
+            }
+            if app.config.searches.is_empty() {
+                lines.push(Line::from(
+                    "Nothing saved yet. Filter with (:) then save it here",
+                ));
+            }
+            if *naming {
+                lines.push(Line::default());
+                lines.push(Line::from(format!(
+                    "Save \"{}\" as: {}▌",
+                    app.filter.as_deref().unwrap_or_default(),
+                    app.search_name
+                )));
+            }
+            let message_text = Paragraph::new(lines).wrap(Wrap::default()).block(
+                Block::default()
+                    .title("Saved searches")
+                    .borders(Borders::ALL)
+                    .padding(Padding::horizontal(1))
+                    .style(app.theme.popup()),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(message_text, area);
+            if *naming {
+                render_buttons(
+                    f,
+                    app,
+                    area,
+                    &[
+                        ("Save", Button::Key(KeyCode::Enter)),
+                        ("Cancel", Button::Key(KeyCode::Esc)),
+                    ],
+                );
+            } else {
+                render_buttons(
+                    f,
+                    app,
+                    area,
+                    &[
+                        ("Apply", Button::Action(Action::Select)),
+                        ("Delete", Button::Action(Action::Delete)),
+                        ("Close", Button::Action(Action::Back)),
+                    ],
+                );
+            }
+        }
+        Popup::Replace => {
+            let area = centered_rect(60, 40, f.size());
+            let buffer = &app.replace_buffer;
+            let popup_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(1)
+                .constraints([
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Min(1),
+                ])
+                .split(area);
+
+            let active_style = app.theme.matched();
+            let fields = [
+                ("Find", &buffer.find),
+                ("Replace with", &buffer.with),
+                ("Dates (e.g. Q3 2023, blank for all)", &buffer.dates),
+            ];
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Block::default()
+                    .title("Find and replace")
+                    .borders(Borders::ALL)
+                    .style(app.theme.popup()),
+                area,
+            );
+            for (index, (title, value)) in fields.into_iter().enumerate() {
+                let mut block = Block::default().title(title).borders(Borders::ALL);
+                if index == buffer.currently_selected as usize {
+                    block = block.style(active_style);
+                }
+                f.render_widget(
+                    Paragraph::new(value.as_str()).block(block),
+                    popup_chunks[index],
+                );
+            }
+            let check = |on: bool| if on { "[x]" } else { "[ ]" };
+            let mut lines = vec![Line::from(format!(
+                "{} regex (ctrl-r)   {} current filter only (ctrl-f)",
+                check(buffer.regex),
+                check(buffer.filtered_only)
+            ))];
+            if let Some(error) = &buffer.error {
+                lines.push(Line::from(Span::styled(
+                    error.as_str(),
+                    app.theme.danger().bold(),
+                )));
+            }
+            f.render_widget(
+                Paragraph::new(lines)
+                    .wrap(Wrap::default())
+                    .block(Block::default().padding(Padding::horizontal(1))),
+                popup_chunks[3],
+            );
+            render_buttons(
+                f,
+                app,
+                area,
+                &[
+                    ("Preview", Button::Key(KeyCode::Enter)),
+                    ("Cancel", Button::Key(KeyCode::Esc)),
+                ],
+            );
+        }
+        Popup::Palette(selected) => {
+            let area = centered_rect(50, 60, f.size());
+            let block = Block::default()
+                .title("Commands")
+                .borders(Borders::ALL)
+                .padding(Padding::horizontal(1))
+                .style(app.theme.popup());
+            let inner = block.inner(area);
+            let popup_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(2), Constraint::Min(1)])
+                .split(inner);
+            let commands = app.palette_commands();
+            let current = app.screen_context();
+            let key_width = commands
+                .iter()
+                .filter_map(|command| command.key.as_ref())
+                .map(|key| key.chars().count())
+                .max()
+                .unwrap_or(0);
+            let items: Vec<ListItem> = commands
+                .iter()
+                .map(|command| {
+                    let text = format!(
+                        "{:<key_width$}  {}",
+                        command.key.as_deref().unwrap_or_default(),
+                        command.action.description()
+                    );
+                    // Commands from other screens say where they'll take you
+                    if command.context == current {
+                        ListItem::new(text)
+                    } else {
+                        ListItem::new(Line::from(vec![
+                            Span::raw(text),
+                            Span::styled(
+                                format!(" ({})", command.context.name()),
+                                app.theme.muted(),
+                            ),
+                        ]))
+                    }
+                })
+                .collect();
+            f.render_widget(Clear, area);
+            f.render_widget(block, area);
+            f.render_widget(
+                Paragraph::new(format!("> {}▌", app.palette_query)),
+                popup_chunks[0],
+            );
+            if items.is_empty() {
+                f.render_widget(Paragraph::new("No matching commands"), popup_chunks[1]);
+            } else {
+                let mut state = ListState::default().with_selected(Some(*selected));
+                f.render_stateful_widget(
+                    List::new(items).highlight_style(app.theme.matched()),
+                    popup_chunks[1],
+                    &mut state,
+                );
+            }
+            render_buttons(
+                f,
+                app,
+                area,
+                &[
+                    ("Run", Button::Key(KeyCode::Enter)),
+                    ("Close", Button::Key(KeyCode::Esc)),
+                ],
+            );
+        }
+        Popup::ReplacePreview(scroll) => {
+            let area = centered_rect(80, 80, f.size());
+            let changes = &app.replace_changes;
+            let mut lines = Vec::<Line>::new();
+            let mut day_count = 0;
+            for (index, change) in changes.iter().enumerate() {
+                if index == 0 || changes[index - 1].date != change.date {
+                    day_count += 1;
+                    if index > 0 {
+                        lines.push(Line::default());
+                    }
+                    lines.push(Line::from(Span::styled(
+                        change.date.format("%d/%m/%Y").to_string(),
+                        Style::default().bold(),
+                    )));
+                }
+                lines.push(Line::from(Span::styled(
+                    format!("{:>4} - {}", change.row + 1, change.before),
+                    app.theme.danger(),
+                )));
+                lines.push(Line::from(Span::styled(
+                    format!("{:>4} + {}", change.row + 1, change.after),
+                    app.theme.success(),
+                )));
+            }
+            let title = format!("Replace {} lines in {} days?", changes.len(), day_count);
+            let preview = Paragraph::new(lines).scroll((*scroll, 0)).block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .padding(Padding::horizontal(1))
+                    .style(app.theme.text()),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(preview, area);
+            render_buttons(
+                f,
+                app,
+                area,
+                &[
+                    ("Replace all", Button::Action(Action::Confirm)),
+                    ("Back", Button::Action(Action::Back)),
+                ],
+            );
         }
     }
 }
@@ -159,27 +601,45 @@ fn render_title(f: &mut Frame, app: &App, rect: Rect) {
         "Engineering Log"
     };
 
-    let title = Paragraph::new(Text::styled(
-        title_message,
-        Style::default().fg(Color::White).bold(),
-    ))
-    .block(title_block.clone())
-    .alignment(Alignment::Center);
+    let title = Paragraph::new(Text::styled(title_message, app.theme.text().bold()))
+        .block(title_block.clone())
+        .alignment(Alignment::Center);
 
     let content = &match app.current_screen {
-        CurrentScreen::Main(_) => "All days".to_string(),
-        CurrentScreen::ViewingDay => app.filtered_days().collect::<Vec<_>>()
-            [app.currently_selected]
-            .date
-            .format("%-d %B, %C%y")
+        CurrentScreen::Main(_) if app.fuzzy_pattern().is_some() => {
+            "Ranked by fuzzy match".to_string()
+        }
+        CurrentScreen::Main(_) => app
+            .query
+            .as_ref()
+            .map_or(DateRange::all(), |query| query.date_range())
             .to_string(),
+        CurrentScreen::ViewingDay => app.selected_day().map_or(String::new(), |day| {
+            day.date.format("%-d %B, %C%y").to_string()
+        }),
+        CurrentScreen::People => "People".to_string(),
+        CurrentScreen::Calendar | CurrentScreen::Heatmap => {
+            app.calendar_date.format("%-d %B, %C%y").to_string()
+        }
+        CurrentScreen::SearchResults => format!(
+            "{} matching lines for {}",
+            app.search_results.len(),
+            app.filter.as_deref().unwrap_or_default()
+        ),
+        CurrentScreen::Stats => format!(
+            "Stats for {}",
+            app.query
+                .as_ref()
+                .map_or(DateRange::all(), |query| query.date_range())
+        ),
+        CurrentScreen::Projects => match app.selected_project() {
+            Some(project) => format!("Project: {}", project),
+            None => "Projects".to_string(),
+        },
     };
-    let subtitle = Paragraph::new(Text::styled(
-        content,
-        Style::default().fg(Color::White).bold(),
-    ))
-    .block(title_block)
-    .alignment(Alignment::Center);
+    let subtitle = Paragraph::new(Text::styled(content, app.theme.text().bold()))
+        .block(title_block)
+        .alignment(Alignment::Center);
 
     f.render_widget(subtitle, title_chunks[0]);
     f.render_widget(title, title_chunks[1]);
@@ -188,59 +648,34 @@ fn render_title(f: &mut Frame, app: &App, rect: Rect) {
 pub fn render_body(f: &mut Frame, app: &mut App, rect: Rect) {
     let mut list_items = Vec::<ListItem>::new();
     match app.current_screen {
-        CurrentScreen::ViewingDay => {
-            f.render_widget(app.text_buffer.widget(), rect);
-        }
+        CurrentScreen::ViewingDay => render_day(f, app, rect),
         CurrentScreen::Main(_) => {
-            let current = app.currently_selected as isize;
-            // Think of a better way to handle this initialisation
-            if app.max_index == -1 {
-                app.max_index = rect.height as isize;
-            } else if current + 3 > app.max_index {
-                let max = current + 3;
-                app.max_index = max;
-                app.min_index = max - rect.height as isize;
-            } else if current < app.min_index {
-                app.max_index = current + rect.height as isize;
-                app.min_index = current;
-            }
-            for (index, day) in app.filtered_days().enumerate() {
-                let index = index as isize;
-
-                if index < app.min_index || index > app.max_index {
-                    continue;
-                }
-
-                if index == current {
-                    let list_item = ListItem::new(Line::from(Span::styled(
-                        day.date_pretty(),
-                        Style::default().fg(Color::White).bg(Color::Blue),
-                    )));
-                    list_items.push(list_item.bold());
-                } else {
-                    let list_item = ListItem::new(Line::from(Span::styled(
-                        day.date_pretty(),
-                        Style::default().fg(Color::White),
-                    )));
-                    list_items.push(list_item);
-                }
+            for day in app.filtered_days() {
+                list_items.push(ListItem::new(day_row(app, day)));
             }
             let scrollbar = Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight);
             let mut scrollbar_state =
-                ScrollbarState::new(app.days.days.len()).position(app.currently_selected);
+                ScrollbarState::new(app.filtered.len()).position(app.currently_selected);
             let layout = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([
-                    Constraint::Min(10),
+                    list_constraint(app),
                     Constraint::Percentage(70),
                     Constraint::Length(2),
                 ])
                 .split(rect);
             f.render_stateful_widget(scrollbar, layout[2], &mut scrollbar_state);
-            if !app.days.days.is_empty() {
+            if let Some(pattern) = app.fuzzy_pattern() {
+                render_fuzzy_preview(f, app, pattern, layout[1]);
+            } else if !app.days.days.is_empty() {
                 app.load_text();
-                app.text_buffer.set_cursor_style(Style::default());
-                f.render_widget(app.text_buffer.widget(), layout[1]);
+                if app.read_mode {
+                    render_markdown(f, app, 0, layout[1]);
+                } else {
+                    app.text_buffer.set_cursor_style(Style::default());
+                    f.render_widget(app.text_buffer.widget(), layout[1]);
+                    render_highlights(f, app, layout[1]);
+                }
             }
             if list_items.is_empty() {
                 let placeholder_text = Paragraph::new("Press n to add a day").block(
@@ -251,20 +686,921 @@ pub fn render_body(f: &mut Frame, app: &mut App, rect: Rect) {
                 );
                 f.render_widget(placeholder_text, layout[0]);
             } else {
+                let list_block = Block::default()
+                    .padding(Padding::horizontal(1))
+                    .title(days_title(app))
+                    .borders(Borders::ALL);
+                app.click_areas.day_list = Some(list_block.inner(layout[0]));
+                app.click_areas.scrollbar = Some(layout[2]);
                 let list = List::new(list_items)
-                    .block(
-                        Block::default()
-                            .padding(Padding::horizontal(1))
-                            .title("Days")
-                            .borders(Borders::ALL),
+                    .block(list_block)
+                    .style(app.theme.text())
+                    .highlight_style(app.theme.selected());
+                // The list works out its own scroll offset from the selection and the space it has
+                app.list_state.select(Some(app.currently_selected));
+                f.render_stateful_widget(list, layout[0], &mut app.list_state);
+            }
+        }
+        CurrentScreen::Projects => render_projects(f, app, rect),
+        CurrentScreen::People => render_people(f, app, rect),
+        CurrentScreen::SearchResults => render_search_results(f, app, rect),
+        CurrentScreen::Calendar => render_calendar(f, app, rect),
+        CurrentScreen::Heatmap => render_heatmap(f, app, rect),
+        CurrentScreen::Stats => render_stats(f, app, rect),
+    }
+}
+
+// The text area can only highlight a regex, so fuzzy matches get a plain paragraph
+fn render_fuzzy_preview(f: &mut Frame, app: &App, pattern: &str, rect: Rect) {
+    let highlight = app.theme.matched();
+    let lines: Vec<Line> = app
+        .selected_day()
+        .map(|day| {
+            day.content
+                .iter()
+                .map(|line| {
+                    let matched = matched_chars(line, pattern);
+                    Line::from(
+                        line.chars()
+                            .enumerate()
+                            .map(|(index, c)| {
+                                if matched.binary_search(&index).is_ok() {
+                                    Span::styled(c.to_string(), highlight)
+                                } else {
+                                    Span::raw(c.to_string())
+                                }
+                            })
+                            .collect::<Vec<_>>(),
                     )
-                    .style(Style::default().fg(Color::White));
-                f.render_widget(list, layout[0]);
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let preview = Paragraph::new(lines).style(app.theme.text()).block(
+        Block::default()
+            .title("Note")
+            .borders(Borders::ALL)
+            .padding(Padding::horizontal(1)),
+    );
+    f.render_widget(preview, rect);
+}
+
+fn highlighted_line<'a>(line: &'a str, ranges: &[(usize, usize)], style: Style) -> Line<'a> {
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for (start, end) in ranges.iter().copied() {
+        if start < last {
+            continue;
+        }
+        spans.push(Span::raw(&line[last..start]));
+        spans.push(Span::styled(&line[start..end], style));
+        last = end;
+    }
+    spans.push(Span::raw(&line[last..]));
+    Line::from(spans)
+}
+
+fn search_result_item<'a>(app: &'a App, hit: &SearchHit) -> ListItem<'a> {
+    let mut lines = vec![Line::from(Span::styled(
+        format!("{} line {}", hit.date.format("%d/%m/%Y"), hit.row + 1),
+        app.theme.accent(),
+    ))];
+    if let Some(day) = app.days.get(hit.date) {
+        let context = app.config.search_context;
+        let first = hit.row.saturating_sub(context);
+        let last = (hit.row + context).min(day.content.len().saturating_sub(1));
+        for row in first..=last {
+            let line = day.content[row].as_str();
+            if row == hit.row {
+                lines.push(highlighted_line(line, &hit.ranges, app.theme.matched()));
+            } else {
+                lines.push(Line::from(Span::styled(line, app.theme.muted())));
+            }
+        }
+    }
+    lines.push(Line::default());
+    ListItem::new(lines)
+}
+
+fn render_search_results(f: &mut Frame, app: &mut App, rect: Rect) {
+    let block = Block::default()
+        .title("Results")
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1));
+    if app.search_results.is_empty() {
+        let placeholder = Paragraph::new(
+            "No matching lines. Results need some text, #tag or @person in the filter",
+        )
+        .wrap(Wrap::default())
+        .block(block);
+        f.render_widget(placeholder, rect);
+        return;
+    }
+    let items: Vec<ListItem> = app
+        .search_results
+        .iter()
+        .map(|hit| search_result_item(app, hit))
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .style(app.theme.text())
+        .highlight_symbol("> ");
+    // The list borrows the results, so the state is rendered from a copy
+    let mut state = app.results_state.clone();
+    f.render_stateful_widget(list, rect, &mut state);
+    app.results_state = state;
+}
+
+fn render_calendar(f: &mut Frame, app: &App, rect: Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rect);
+
+    let selected = app.calendar_date;
+    let today = chrono::Local::now().date_naive();
+    let weeks = month_grid(selected);
+    let month_days: Vec<NaiveDate> = weeks.iter().flatten().flatten().copied().collect();
+    let max = match (month_days.first(), month_days.last()) {
+        (Some(first), Some(last)) => app.days.max_activity(*first, *last, Activity::Words),
+        _ => 0,
+    };
+    // Room for the day number and its word count when the terminal is wide enough
+    let cell_width = ((layout[0].width.saturating_sub(4)) / 7).clamp(4, 12) as usize;
+
+    let mut lines = vec![Line::from(
+        ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+            .iter()
+            .map(|name| Span::styled(format!("{:<cell_width$}", name), Style::default().bold()))
+            .collect::<Vec<_>>(),
+    )];
+    for week in weeks.iter() {
+        let mut spans = Vec::new();
+        for cell in week.iter() {
+            let date = match cell {
+                Some(date) => *date,
+                None => {
+                    spans.push(Span::raw(" ".repeat(cell_width)));
+                    continue;
+                }
+            };
+            let words = app.days.activity_on(date, Activity::Words);
+            let text = if words > 0 && cell_width >= 7 {
+                format!("{:>2} {:<w$}", date.day(), words, w = cell_width - 3)
+            } else {
+                format!("{:>2}{:w$}", date.day(), "", w = cell_width - 2)
+            };
+            let mut style = app.theme.activity(activity_level(words, max));
+            if !app.days.contains_day(date) {
+                style = style.patch(app.theme.muted());
+            }
+            if date == today {
+                style = style.bold().underlined();
+            }
+            if date == selected {
+                style = style.patch(app.theme.matched()).bold();
+            }
+            spans.push(Span::styled(text, style));
+        }
+        lines.push(Line::from(spans));
+        lines.push(Line::default());
+    }
+    let calendar = Paragraph::new(lines).block(
+        Block::default()
+            .title(selected.format("%B %Y").to_string())
+            .borders(Borders::ALL)
+            .padding(Padding::horizontal(1)),
+    );
+    f.render_widget(calendar, layout[0]);
+
+    let preview_block = Block::default()
+        .title(format!(
+            "{} words",
+            app.days.activity_on(selected, Activity::Words)
+        ))
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1));
+    let preview = match app.days.get(selected) {
+        Some(day) => Paragraph::new(day.content.join("\n")),
+        None => Paragraph::new("Nothing written yet. Press enter to start this day"),
+    };
+    f.render_widget(
+        preview
+            .wrap(Wrap::default())
+            .style(app.theme.text())
+            .block(preview_block),
+        layout[1],
+    );
+}
+
+// Without colour the heatmap is shaded with block characters instead
+const PLAIN_SHADES: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+fn render_heatmap(f: &mut Frame, app: &App, rect: Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(10), Constraint::Min(3)])
+        .split(rect);
+
+    let selected = app.calendar_date;
+    let activity = app.heatmap_activity;
+    let today = chrono::Local::now().date_naive();
+    let weeks = year_grid(selected);
+    let year_days: Vec<NaiveDate> = weeks.iter().flatten().flatten().copied().collect();
+    let (first, last) = match (year_days.first(), year_days.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return,
+    };
+    let max = app.days.max_activity(first, last, activity);
+    let label_width = 4;
+    // Squares are two columns wide unless the terminal is too narrow for a whole year of them
+    let cell_width =
+        if (layout[0].width as usize).saturating_sub(label_width + 4) >= weeks.len() * 2 {
+            2
+        } else {
+            1
+        };
+
+    let mut month_labels = " ".repeat(label_width + weeks.len() * cell_width);
+    for (column, week) in weeks.iter().enumerate() {
+        if let Some(first_of) = week.iter().flatten().find(|d| d.day() == 1) {
+            let start = label_width + column * cell_width;
+            let label = first_of.format("%b").to_string();
+            if start + label.len() <= month_labels.len() {
+                month_labels.replace_range(start..start + label.len(), &label);
+            }
+        }
+    }
+    let mut lines = vec![Line::from(Span::styled(
+        month_labels,
+        Style::default().bold(),
+    ))];
+    for (row, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{:<label_width$}", label))];
+        for week in weeks.iter() {
+            let date = match week[row] {
+                Some(date) => date,
+                None => {
+                    spans.push(Span::raw(" ".repeat(cell_width)));
+                    continue;
+                }
+            };
+            let level = activity_level(app.days.activity_on(date, activity), max);
+            let mut style = app.theme.activity(level);
+            if level == 0 && !app.theme.plain {
+                style = style.bg(app.theme.muted);
+            }
+            if date == selected {
+                style = style.patch(app.theme.matched());
+            }
+            let cell = if date == today {
+                format!("{:<cell_width$}", "•")
+            } else if app.theme.plain {
+                PLAIN_SHADES[level as usize].repeat(cell_width)
+            } else {
+                " ".repeat(cell_width)
+            };
+            spans.push(Span::styled(cell, style));
+        }
+        lines.push(Line::from(spans));
+    }
+    let heatmap = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!("{} ({})", selected.format("%Y"), activity.name()))
+            .borders(Borders::ALL)
+            .padding(Padding::horizontal(1)),
+    );
+    f.render_widget(heatmap, layout[0]);
+
+    let written: Vec<&Day> = app
+        .days
+        .iter()
+        .filter(|day| day.date >= first && day.date <= last && activity.of(day) > 0)
+        .collect();
+    let total: usize = written.iter().map(|day| activity.of(day)).sum();
+    // Longest run of days with nothing written, up to today for the current year
+    let mut longest_gap = (0, first);
+    let mut gap = 0;
+    for date in year_days.iter().filter(|date| **date <= today) {
+        if app.days.activity_on(*date, activity) == 0 {
+            gap += 1;
+            if gap > longest_gap.0 {
+                longest_gap = (gap, *date - chrono::Duration::days(gap - 1));
+            }
+        } else {
+            gap = 0;
+        }
+    }
+    let mut details = vec![
+        Line::from(Span::styled(
+            format!(
+                "{}: {} {}",
+                selected.format("%A %-d %B"),
+                app.days.activity_on(selected, activity),
+                activity.name()
+            ),
+            Style::default().bold(),
+        )),
+        Line::from(format!(
+            "{} days written | {} {} in total",
+            written.len(),
+            total,
+            activity.name()
+        )),
+    ];
+    if let Some(busiest) = written.iter().max_by_key(|day| activity.of(day)) {
+        details.push(Line::from(format!(
+            "Busiest day: {} ({} {})",
+            busiest.date.format("%-d %B"),
+            activity.of(busiest),
+            activity.name()
+        )));
+    }
+    if longest_gap.0 > 0 {
+        details.push(Line::from(format!(
+            "Longest gap: {} days from {}",
+            longest_gap.0,
+            longest_gap.1.format("%-d %B")
+        )));
+    }
+    let details = Paragraph::new(details)
+        .wrap(Wrap::default())
+        .style(app.theme.text())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .padding(Padding::horizontal(1)),
+        );
+    f.render_widget(details, layout[1]);
+}
+
+fn render_stats(f: &mut Frame, app: &App, rect: Rect) {
+    let stats = &app.stats;
+    if stats.days == 0 {
+        let empty = Paragraph::new("Nothing logged in this range")
+            .style(app.theme.muted())
+            .block(Block::default().borders(Borders::ALL).title("Stats"));
+        f.render_widget(empty, rect);
+        return;
+    }
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(11),
+            Constraint::Min(6),
+            Constraint::Length(5),
+            Constraint::Length(8),
+        ])
+        .split(rect);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(34),
+            Constraint::Percentage(22),
+            Constraint::Percentage(22),
+            Constraint::Percentage(22),
+        ])
+        .split(rows[0]);
+
+    let busiest: Vec<String> = stats
+        .busiest_weekdays()
+        .iter()
+        .take(3)
+        .map(|(weekday, _)| weekday.to_string())
+        .collect();
+    let summary = Paragraph::new(vec![
+        Line::from(format!("{} days logged", stats.days)),
+        Line::from(format!("{} words", stats.words)),
+        Line::from(format!(
+            "{} words a day on average",
+            stats.words / stats.days
+        )),
+        Line::from(""),
+        Line::from(format!("Current streak: {} weekdays", stats.current_streak)),
+        Line::from(format!("Longest streak: {} weekdays", stats.longest_streak)),
+        Line::from(""),
+        Line::from(format!("Busiest: {}", busiest.join(", "))),
+    ])
+    .wrap(Wrap::default())
+    .style(app.theme.text())
+    .block(Block::default().borders(Borders::ALL).title("Summary"));
+    f.render_widget(summary, top[0]);
+
+    for (names, title, rect) in [
+        (&stats.top_tags, "Tags", top[1]),
+        (&stats.top_people, "People", top[2]),
+    ] {
+        let items: Vec<ListItem> = names
+            .iter()
+            .map(|(name, count)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:>3} ", count), app.theme.muted()),
+                    Span::raw(name.as_str()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .style(app.theme.text())
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(list, rect);
+    }
+
+    let weekday_names = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+    let weekdays: Vec<(&str, u64)> = weekday_names
+        .iter()
+        .copied()
+        .zip(stats.weekdays.iter().copied())
+        .collect();
+    let weekdays = BarChart::default()
+        .data(&weekdays)
+        .bar_width(2)
+        .bar_gap(1)
+        .bar_style(app.theme.accent())
+        .value_style(app.theme.accent().reversed())
+        .label_style(app.theme.muted())
+        .block(Block::default().borders(Borders::ALL).title("Weekdays"));
+    f.render_widget(weekdays, top[3]);
+
+    let points: Vec<(f64, f64)> = stats
+        .days_per_month
+        .iter()
+        .enumerate()
+        .map(|(i, (_, count))| (i as f64, *count as f64))
+        .collect();
+    let most = stats
+        .days_per_month
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let month_label = |i: usize| {
+        stats
+            .days_per_month
+            .get(i)
+            .map_or(String::new(), |(month, _)| {
+                month.format("%b %Y").to_string()
+            })
+    };
+    let datasets = vec![Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(app.theme.accent())
+        .data(&points)];
+    let months = Chart::new(datasets)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Days logged per month"),
+        )
+        .x_axis(
+            Axis::default()
+                .style(app.theme.muted())
+                .bounds([0.0, (points.len().max(2) - 1) as f64])
+                .labels(vec![
+                    Span::raw(month_label(0)),
+                    Span::raw(month_label(points.len() - 1)),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(app.theme.muted())
+                .bounds([0.0, most as f64])
+                .labels(vec![Span::raw("0"), Span::raw(most.to_string())]),
+        );
+    f.render_widget(months, rows[1]);
+
+    // As many of the latest days as fit, one column each
+    let shown = rows[2].width.saturating_sub(2) as usize;
+    let words: Vec<u64> = stats
+        .words_per_day
+        .iter()
+        .skip(stats.words_per_day.len().saturating_sub(shown))
+        .map(|(_, words)| *words)
+        .collect();
+    let words = Sparkline::default()
+        .data(&words)
+        .style(app.theme.accent())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Words per day"),
+        );
+    f.render_widget(words, rows[2]);
+
+    // Bars are three columns wide plus a gap, labelled with the week of the year
+    let shown = rows[3].width.saturating_sub(2) as usize / 4;
+    let labels: Vec<String> = stats
+        .words_per_week
+        .iter()
+        .map(|(monday, _)| format!("{:02}", monday.iso_week().week()))
+        .collect();
+    let weeks: Vec<(&str, u64)> = labels
+        .iter()
+        .map(String::as_str)
+        .zip(stats.words_per_week.iter().map(|(_, words)| *words))
+        .skip(labels.len().saturating_sub(shown))
+        .collect();
+    let weeks = BarChart::default()
+        .data(&weeks)
+        .bar_width(3)
+        .bar_gap(1)
+        .bar_style(app.theme.accent())
+        .value_style(app.theme.accent().reversed())
+        .label_style(app.theme.muted())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Words per week"),
+        );
+    f.render_widget(weeks, rows[3]);
+}
+
+fn render_people(f: &mut Frame, app: &App, rect: Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(25), Constraint::Percentage(75)])
+        .split(rect);
+
+    let people = &app.people;
+    let people_block = Block::default()
+        .title("People")
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1));
+    if people.is_empty() {
+        let placeholder = Paragraph::new("Mention someone with @name to see them here")
+            .wrap(Wrap::default())
+            .block(people_block);
+        f.render_widget(placeholder, layout[0]);
+        return;
+    }
+
+    let items: Vec<ListItem> = people
+        .iter()
+        .enumerate()
+        .map(|(index, person)| {
+            let text = format!("@{} ({})", person.name, person.days);
+            if index == app.person_selected {
+                ListItem::new(text).style(app.theme.selected())
+            } else {
+                ListItem::new(text)
+            }
+        })
+        .collect();
+    let list = List::new(items).block(people_block).style(app.theme.text());
+    f.render_widget(list, layout[0]);
+
+    let mut lines = Vec::<Line>::new();
+    if let Some(person) = people.get(app.person_selected) {
+        lines.push(Line::from(format!(
+            "First: {} | Last: {} | Days: {}",
+            person.first.format("%d/%m/%Y"),
+            person.last.format("%d/%m/%Y"),
+            person.days
+        )));
+        lines.push(Line::default());
+        for (date, line) in person.lines.iter() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{} ", date.format("%d/%m/%Y")), app.theme.accent()),
+                Span::raw(line.as_str()),
+            ]));
+        }
+    }
+    let mentions = Paragraph::new(lines)
+        .style(app.theme.text())
+        .wrap(Wrap { trim: false })
+        .scroll((app.people_scroll, 0))
+        .block(
+            Block::default()
+                .title("Mentions")
+                .borders(Borders::ALL)
+                .padding(Padding::horizontal(1)),
+        );
+    f.render_widget(mentions, layout[1]);
+}
+
+fn render_day(f: &mut Frame, app: &mut App, rect: Rect) {
+    let backlinks = match app.selected_day() {
+        Some(day) => app.days.backlinks(day.date),
+        None => Vec::new(),
+    };
+    let note_rect = if backlinks.is_empty() {
+        rect
+    } else {
+        let layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+            .split(rect);
+        render_backlinks(f, &app.theme, backlinks, layout[1]);
+        layout[0]
+    };
+    app.click_areas.editor = Some(
+        app.text_buffer
+            .block()
+            .map_or(note_rect, |block| block.inner(note_rect)),
+    );
+    if app.read_mode {
+        render_markdown(f, app, app.read_scroll, note_rect);
+    } else if app.config.soft_wrap {
+        render_soft_wrapped(f, app, note_rect);
+    } else {
+        f.render_widget(app.text_buffer.widget(), note_rect);
+        render_highlights(f, app, note_rect);
+    }
+}
+
+// The text area can only highlight one search pattern, which filter matches use, so code
+// blocks and links are recoloured after it's drawn
+fn render_highlights(f: &mut Frame, app: &mut App, rect: Rect) {
+    let inner = app
+        .text_buffer
+        .block()
+        .map_or(rect, |block| block.inner(rect));
+    app.measure_editor_scroll();
+
+    let (top, left) = (app.editor_scroll.0 as usize, app.editor_scroll.1 as usize);
+    let tab = app.text_buffer.tab_length() as usize;
+    let lines = app.text_buffer.lines();
+    let rows = top..(top + inner.height as usize).min(lines.len());
+    let code = if app.theme.plain {
+        vec![None; rows.len()]
+    } else {
+        code_spans(lines, &app.theme.code_theme, rows.clone())
+    };
+    let buffer = f.buffer_mut();
+    for (row, (line, spans)) in lines[rows].iter().zip(code).enumerate() {
+        let mut styles: Vec<Option<Style>> = spans
+            .into_iter()
+            .flatten()
+            .flat_map(|(style, text)| text.chars().map(move |_| Some(style)).collect::<Vec<_>>())
+            .collect();
+        styles.resize(line.chars().count(), None);
+        for (_, start, end) in links(line) {
+            styles[start..end].fill(Some(app.theme.link()));
+        }
+        let y = inner.y + row as u16;
+        // Cells each character covers as the text area draws it: tabs reach the next stop
+        // and wide characters take two
+        let mut column = 0;
+        for (c, style) in line.chars().zip(styles) {
+            let width = match c {
+                '\t' if tab > 0 => tab - column % tab,
+                '\t' => 0,
+                c => c.width().unwrap_or(0),
+            };
+            let cells = column..column + width;
+            column += width;
+            let Some(style) = style else {
+                continue;
+            };
+            for x in cells.filter(|x| *x >= left) {
+                if x - left >= inner.width as usize {
+                    break;
+                }
+                let cell = buffer.get_mut(inner.x + (x - left) as u16, y);
+                // Leave the cursor, selection and filter matches as they are
+                if cell.bg == Color::Reset && !cell.modifier.contains(Modifier::REVERSED) {
+                    cell.set_style(style);
+                }
             }
         }
     }
 }
 
+// The text area can't wrap lines, so soft wrap draws the day itself. Only the display
+// changes: the lines are stored as they were typed and the cursor moves through them as usual
+fn render_soft_wrapped(f: &mut Frame, app: &mut App, rect: Rect) {
+    let block = app.text_buffer.block().cloned().unwrap_or_default();
+    let inner = block.inner(rect);
+    let lines = app.text_buffer.lines();
+    let cursor = app.text_buffer.cursor();
+    let selection = app.vim.selection(cursor);
+    let highlighted = if app.theme.plain {
+        vec![None; lines.len()]
+    } else {
+        code_spans(lines, &app.theme.code_theme, 0..lines.len())
+    };
+
+    let mut rows = Vec::new();
+    let mut cursor_at = (0, 0);
+    for (row, (line, code)) in lines.iter().zip(highlighted).enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        let mut styles: Vec<Style> = match code {
+            Some(spans) => spans
+                .into_iter()
+                .flat_map(|(style, text)| text.chars().map(move |_| style).collect::<Vec<_>>())
+                .collect(),
+            None => Vec::new(),
+        };
+        styles.resize(chars.len(), app.theme.text());
+        for (_, start, end) in links(line) {
+            styles[start..end].fill(app.theme.link());
+        }
+        // Filter matches, as the text area would show them
+        if let Some(pattern) = app.text_buffer.search_pattern() {
+            for found in pattern.find_iter(line) {
+                let start = line[..found.start()].chars().count();
+                let end = start + found.as_str().chars().count();
+                for style in &mut styles[start..end] {
+                    *style = app.text_buffer.search_style();
+                }
+            }
+        }
+        if let Some((start, end)) = selection {
+            for (col, style) in styles.iter_mut().enumerate() {
+                if start <= (row, col) && (row, col) < end {
+                    *style = app.theme.selected();
+                }
+            }
+        }
+        for (start, end) in wrap_line(line, inner.width as usize) {
+            if row == cursor.0 && start <= cursor.1 && (cursor.1 < end || end == chars.len()) {
+                let before: String = chars[start..cursor.1].iter().collect();
+                cursor_at = (rows.len(), columns(&before));
+            }
+            let mut spans: Vec<Span> = Vec::new();
+            for col in start..end {
+                match spans.last_mut() {
+                    Some(span) if span.style == styles[col] => {
+                        span.content.to_mut().push(chars[col])
+                    }
+                    _ => spans.push(Span::styled(chars[col].to_string(), styles[col])),
+                }
+            }
+            rows.push(Line::from(spans));
+        }
+    }
+    if lines.len() == 1 && lines[0].is_empty() {
+        rows = vec![Line::from(Span::styled(
+            app.text_buffer.placeholder_text().to_string(),
+            app.theme.muted(),
+        ))];
+    }
+
+    let top = next_scroll_top(app.editor_scroll.0, cursor_at.0 as u16, inner.height);
+    app.editor_scroll = (top, 0);
+    f.render_widget(
+        Paragraph::new(rows)
+            .style(app.theme.text())
+            .scroll((top, 0))
+            .block(block),
+        rect,
+    );
+    let x = inner.x + (cursor_at.1 as u16).min(inner.width.saturating_sub(1));
+    let y = inner.y + cursor_at.0 as u16 - top;
+    if inner.width > 0 && y < inner.bottom() {
+        let cell = f.buffer_mut().get_mut(x, y);
+        cell.set_style(cell.style().add_modifier(Modifier::REVERSED));
+    }
+}
+
+// Scrolls only as far as it takes to keep the cursor in view
+fn next_scroll_top(previous: u16, cursor: u16, length: u16) -> u16 {
+    if cursor < previous {
+        cursor
+    } else if previous + length <= cursor {
+        cursor + 1 - length
+    } else {
+        previous
+    }
+}
+
+// The text area is only for editing, so read mode draws the markdown itself
+fn render_markdown(f: &mut Frame, app: &App, scroll: u16, rect: Rect) {
+    let rendered = Paragraph::new(render(app.text_buffer.lines(), &app.theme))
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0))
+        .block(
+            Block::default()
+                .title("Note (read)")
+                .style(app.theme.text())
+                .borders(Borders::ALL)
+                .padding(Padding::horizontal(1)),
+        );
+    f.render_widget(rendered, rect);
+}
+
+fn render_backlinks(f: &mut Frame, theme: &Theme, backlinks: Vec<(NaiveDate, &str)>, rect: Rect) {
+    let items: Vec<ListItem> = backlinks
+        .into_iter()
+        .map(|(date, line)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", date.format("%d/%m/%Y")), theme.accent()),
+                Span::raw(line.trim()),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title("Backlinks")
+                .borders(Borders::ALL)
+                .padding(Padding::horizontal(1)),
+        )
+        .style(theme.text());
+    f.render_widget(list, rect);
+}
+
+// Metadata fields are shown as columns after the date
+fn day_row(app: &App, day: &Day) -> String {
+    let mut row = day.date_pretty();
+    for field in app.config.fields.iter() {
+        let value = day
+            .meta_value(&field.name)
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let value: String = value.chars().take(field.width()).collect();
+        row.push_str(&format!(" {:>width$}", value, width = field.width()));
+    }
+    row
+}
+
+fn days_title(app: &App) -> String {
+    if app.config.fields.is_empty() {
+        return "Days".to_string();
+    }
+    let mut title = format!(" {:<10}", "Days");
+    for field in app.config.fields.iter() {
+        let name: String = field.name.chars().take(field.width()).collect();
+        title.push_str(&format!(" {:>width$}", name, width = field.width()));
+    }
+    title
+}
+
+fn list_constraint(app: &App) -> Constraint {
+    // Always room for the whole date, however narrow the terminal gets
+    if app.config.fields.is_empty() {
+        Constraint::Min(14)
+    } else {
+        let columns: usize = app.config.fields.iter().map(|f| f.width() + 1).sum();
+        // Date, borders and padding
+        Constraint::Length((columns + 14) as u16)
+    }
+}
+
+fn render_projects(f: &mut Frame, app: &App, rect: Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(25), Constraint::Percentage(75)])
+        .split(rect);
+
+    let projects_block = Block::default()
+        .title("Projects")
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1));
+    if app.config.projects.is_empty() {
+        let placeholder = Paragraph::new("Add projects to the config file to see them here")
+            .wrap(Wrap::default())
+            .block(projects_block);
+        f.render_widget(placeholder, layout[0]);
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .config
+        .projects
+        .iter()
+        .enumerate()
+        .map(|(index, project)| {
+            if index == app.project_selected {
+                ListItem::new(project.as_str()).style(app.theme.selected())
+            } else {
+                ListItem::new(project.as_str())
+            }
+        })
+        .collect();
+    let list = List::new(items)
+        .block(projects_block)
+        .style(app.theme.text());
+    f.render_widget(list, layout[0]);
+
+    let mut lines = Vec::<Line>::new();
+    if let Some(project) = app.selected_project() {
+        for (date, content) in app.days.project_timeline(project) {
+            lines.push(Line::from(Span::styled(
+                date.format("%d/%m/%Y").to_string(),
+                app.theme.accent(),
+            )));
+            lines.extend(content.into_iter().map(Line::from));
+            lines.push(Line::default());
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from("Nothing logged for this project yet"));
+    }
+    let timeline = Paragraph::new(lines)
+        .style(app.theme.text())
+        .wrap(Wrap { trim: false })
+        .scroll((app.project_scroll, 0))
+        .block(
+            Block::default()
+                .title("Timeline")
+                .borders(Borders::ALL)
+                .padding(Padding::horizontal(1)),
+        );
+    f.render_widget(timeline, layout[1]);
+}
+
 pub fn render_footer(f: &mut Frame, app: &App, rect: Rect) {
     let footer_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -272,48 +1608,114 @@ pub fn render_footer(f: &mut Frame, app: &App, rect: Rect) {
         .split(rect);
 
     let current_keys_hint = {
-        let text = if let Some(popup) = &app.popup {
-            match popup {
-                Popup::NewDay => "(esc) cancel | (tab | enter) next/save",
-                Popup::ConfDeleteDay => "(esc) cancel | (enter) save | \"y\" save",
-                Popup::Info(_) => "(esc) close",
-                Popup::Config(editing) => {
-                    if *editing {
-                        "(esc) close | (Enter) save | (Esc) cancel"
-                    } else {
-                        "(esc) close | (e) edit"
-                    }
-                }
+        let text = match &app.popup {
+            // Popups that take text keep their keys to themselves
+            Some(Popup::NewDay) => "(esc) cancel | (tab | enter) next/save".to_string(),
+            Some(Popup::Config(true) | Popup::Metadata(_, true) | Popup::SavedSearches(_, true)) => {
+                "(esc) cancel | (enter) save".to_string()
+            }
+            Some(Popup::Palette(_)) => {
+                "(esc) close | (up/down) move | (enter) run | type to search".to_string()
             }
+            Some(Popup::Replace) => {
+                "(esc) cancel | (tab) next field | (ctrl-r) regex | (ctrl-f) filter only | (enter) preview".to_string()
+            }
+            Some(Popup::SavedSearches(_, false)) => format!(
+                "{} | (1-9) apply",
+                key_hints(app, Context::Popup, &app.popup_actions())
+            ),
+            Some(_) => key_hints(app, Context::Popup, &app.popup_actions()),
+            None => {
+                let context = app.key_context();
+                let actions: Vec<Action> = app
+                    .keymap
+                    .actions(context)
+                    .iter()
+                    .map(|(action, _)| *action)
+                    .filter(|action| app.action_available(*action))
+                    .collect();
+                key_hints(app, context, &actions)
+            }
+        };
+        // Show the start of a multi-key binding until it's finished
+        let text = if app.pending_keys.is_empty() {
+            text
         } else {
-            match app.current_screen {
-                CurrentScreen::Main(true) => "(esc) cancel | (enter) done",
-                CurrentScreen::Main(false) => {
-                    //TODO these are getting pretty long.
-                    // Add some code for "extended menu" or maybe split it into two with a toggle
-                    if app.filter.is_some() {
-                        "(q) quit | (enter) edit day | (esc) clear filter | (d) delete day | (n) new day | (i) info | (:) filter | vim motions if you're cool"
-                    } else {
-                        "(q) quit | (enter) edit day | (d) delete day | (n) new day | (i) info | (:) filter | (r) current_day | vim motions if you're cool"
-                    }
-                }
-                CurrentScreen::ViewingDay => "(esc) back",
+            format!("{} … | {}", show_sequence(&app.pending_keys), text)
+        };
+        let text = match app.vim_status() {
+            Some(status) if app.popup.is_none() && app.key_context() == Context::Day => {
+                format!("{} | {}", status, text)
             }
+            _ => text,
         };
-        Span::styled(
-            text,
-            Style::default().bold().fg(Color::White).bg(Color::Blue),
-        )
+        Span::styled(text, app.theme.selected())
     };
     let key_hints_footer = Paragraph::new(Line::from(current_keys_hint))
         .block(Block::default().padding(Padding::horizontal(1)));
 
-    if let CurrentScreen::Main(true) = app.current_screen {
-        f.render_widget(app.filter_buffer.widget(), footer_chunks[0]);
+    match app.current_screen {
+        CurrentScreen::Main(true) => {
+            let status = match &app.filter_error {
+                Some(error) => Some((error.to_string(), app.theme.danger())),
+                None if app.fuzzy => Some(("fuzzy".to_string(), app.theme.accent())),
+                None => None,
+            };
+            match status {
+                Some((message, style)) => {
+                    let filter_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([
+                            Constraint::Min(10),
+                            Constraint::Length(message.chars().count() as u16 + 2),
+                        ])
+                        .split(footer_chunks[0]);
+                    f.render_widget(app.filter_buffer.widget(), filter_chunks[0]);
+                    let status_text = Paragraph::new(Span::styled(message, style.bold()))
+                        .block(Block::default().padding(Padding::horizontal(1)));
+                    f.render_widget(status_text, filter_chunks[1]);
+                }
+                None => f.render_widget(app.filter_buffer.widget(), footer_chunks[0]),
+            }
+        }
+        CurrentScreen::ViewingDay => {
+            let completions = app.mention_completions();
+            if !completions.is_empty() {
+                let mut spans = vec![Span::styled("(tab) complete ", app.theme.text().bold())];
+                for name in completions.into_iter().take(5) {
+                    spans.push(Span::styled(format!("@{} ", name), app.theme.accent()));
+                }
+                let hint = Paragraph::new(Line::from(spans))
+                    .block(Block::default().padding(Padding::horizontal(1)));
+                f.render_widget(hint, footer_chunks[0]);
+            }
+        }
+        _ => {}
     }
     f.render_widget(key_hints_footer, footer_chunks[1]);
 }
 
+// "(key) description" for each action, using the first key it's bound to
+fn key_hints(app: &App, context: Context, actions: &[Action]) -> String {
+    actions
+        .iter()
+        .filter_map(|action| {
+            app.keymap
+                .key_for(context, *action)
+                .map(|key| format!("({}) {}", key, action.description()))
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+// What to press in a yes/no popup, as the config file has it bound
+fn confirm_prompt(app: &App) -> String {
+    match app.keymap.key_for(Context::Popup, Action::Confirm) {
+        Some(key) => format!("{} for yes\nAny other key to cancel", key),
+        None => "Any key to cancel".to_string(),
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
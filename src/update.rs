@@ -1,25 +1,33 @@
-use chrono::NaiveDate;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use chrono::{Duration, NaiveDate};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{backend::Backend, layout::Rect};
 use tokio::sync::mpsc::UnboundedSender;
-use tui_textarea::{Input, Key};
+use tui_textarea::CursorMove;
 
 use crate::{
-    app::{save_inner, App, CurrentScreen, Day, Info, Popup},
+    app::{App, CurrentScreen, Info, Popup},
+    calendar::shift_months,
+    keymap::{Action, Context, Lookup},
+    link::Link,
+    metadata::FieldKind,
+    mouse::{contains, scrollbar_position, Button},
     tui::{Event, Loading, Tui},
 };
 
-pub fn update(event: Event, app: &mut App<'_>, tui: &Tui) {
+pub fn update<B: Backend>(event: Event, app: &mut App<'_>, tui: &Tui<B>) {
     match event {
         Event::Key(key_event) => {
             if let Some(popup) = &app.popup {
-                update_popup(app, key_event, popup.clone());
+                update_popup(app, key_event, popup.clone(), &tui.event_tx);
             } else {
                 update_screen(app, key_event, &tui.event_tx);
             }
         }
         Event::Loading(Loading::Saving(state)) => app.saving = state,
         Event::Loading(Loading::Loading(state)) => app.loading = state,
-        Event::Tick => {}
+        Event::Mouse(mouse) => update_mouse(app, mouse, &tui.event_tx),
+        // The list and text areas keep their selection visible at any size, so a redraw is enough
+        Event::Tick | Event::Resize(..) => {}
         Event::LoadDays(switch_screen) => {
             tui.event_tx
                 .send(Event::Loading(Loading::Loading(true)))
@@ -32,7 +40,112 @@ pub fn update(event: Event, app: &mut App<'_>, tui: &Tui) {
     }
 }
 
-fn update_popup(app: &mut App, key_event: KeyEvent, popup: Popup) {
+const WHEEL_LINES: i16 = 3;
+
+fn update_mouse(app: &mut App, mouse: MouseEvent, tx: &UnboundedSender<Event>) {
+    let (column, row) = (mouse.column, mouse.row);
+    if let Some(popup) = app.popup.clone() {
+        if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+            match app.click_areas.button_at(column, row) {
+                Some(Button::Key(key)) => {
+                    update_popup(app, KeyEvent::new(key, KeyModifiers::NONE), popup, tx)
+                }
+                Some(Button::Action(action)) => popup_action(app, popup, Some(action)),
+                None => {}
+            }
+        }
+        return;
+    }
+    let areas = app.click_areas.clone();
+    let over = |area: Option<Rect>| area.is_some_and(|area| contains(area, column, row));
+    let main = matches!(app.current_screen, CurrentScreen::Main(_));
+    let viewing = app.current_screen == CurrentScreen::ViewingDay;
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) if main && over(areas.scrollbar) => {
+            app.dragging_scrollbar = true;
+            app.currently_selected =
+                scrollbar_position(areas.scrollbar.unwrap_or_default(), row, app.filtered.len());
+        }
+        MouseEventKind::Drag(MouseButton::Left) if main && app.dragging_scrollbar => {
+            app.currently_selected =
+                scrollbar_position(areas.scrollbar.unwrap_or_default(), row, app.filtered.len());
+        }
+        MouseEventKind::Up(MouseButton::Left) => app.dragging_scrollbar = false,
+        MouseEventKind::Down(MouseButton::Left) if main && over(areas.day_list) => {
+            let clicked =
+                app.list_state.offset() + (row - areas.day_list.unwrap_or_default().y) as usize;
+            if clicked < app.filtered.len() {
+                app.currently_selected = clicked;
+            }
+        }
+        MouseEventKind::ScrollDown if main => {
+            for _ in 0..WHEEL_LINES {
+                app.increment_selected();
+            }
+        }
+        MouseEventKind::ScrollUp if main => {
+            for _ in 0..WHEEL_LINES {
+                app.decrement_selected();
+            }
+        }
+        MouseEventKind::ScrollDown | MouseEventKind::ScrollUp if viewing => {
+            let lines = if mouse.kind == MouseEventKind::ScrollDown {
+                WHEEL_LINES
+            } else {
+                -WHEEL_LINES
+            };
+            if app.read_mode {
+                app.read_scroll = app.read_scroll.saturating_add_signed(lines);
+            } else {
+                app.scroll_editor(lines);
+            }
+        }
+        MouseEventKind::Down(MouseButton::Left)
+            if viewing && !app.read_mode && over(areas.editor) =>
+        {
+            let editor = areas.editor.unwrap_or_default();
+            let (top, left) = app.editor_scroll;
+            let (row, column) = if app.config.soft_wrap {
+                app.soft_wrapped_position(top + row - editor.y, column - editor.x, editor.width)
+            } else {
+                (top + row - editor.y, left + column - editor.x)
+            };
+            app.text_buffer.move_cursor(CursorMove::Jump(row, column));
+        }
+        _ => {}
+    }
+}
+
+// Adds a key to any that are waiting and works out what they mean in `context`. When the keys
+// so far can't lead anywhere the new key gets another go on its own, so a stray `g` doesn't
+// swallow the `j` after it. The keys given up on come back first, to be handled as unbound
+fn next_action(app: &mut App, context: Context, key_event: KeyEvent) -> (Vec<KeyEvent>, Lookup) {
+    app.pending_keys.push(key_event.into());
+    match app.keymap.lookup(context, &app.pending_keys) {
+        Lookup::Action(action) => {
+            app.pending_keys.clear();
+            (Vec::new(), Lookup::Action(action))
+        }
+        Lookup::Pending => (Vec::new(), Lookup::Pending),
+        Lookup::Unbound if app.pending_keys.len() > 1 => {
+            app.pending_keys.pop();
+            let mut stranded: Vec<KeyEvent> = app
+                .pending_keys
+                .drain(..)
+                .map(|key| KeyEvent::new(key.code, key.modifiers))
+                .collect();
+            let (more, lookup) = next_action(app, context, key_event);
+            stranded.extend(more);
+            (stranded, lookup)
+        }
+        Lookup::Unbound => {
+            app.pending_keys.clear();
+            (Vec::new(), Lookup::Unbound)
+        }
+    }
+}
+
+fn update_popup(app: &mut App, key_event: KeyEvent, popup: Popup, tx: &UnboundedSender<Event>) {
     match popup {
         Popup::NewDay => match key_event.code {
             KeyCode::Esc => {
@@ -49,11 +162,7 @@ fn update_popup(app: &mut App, key_event: KeyEvent, popup: Popup) {
                         let month = popup_buffer.month.parse().unwrap_or(20000);
                         let day = popup_buffer.day.parse().unwrap_or(20000);
                         if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
-                            let day = Day::new(date);
-                            app.currently_selected = app.days.add(day);
-                            app.current_screen = CurrentScreen::ViewingDay;
-                            app.save();
-                            app.load_text();
+                            app.open_date(date);
                         }
                         app.popup_buffer.clear();
                         app.popup = None;
@@ -67,129 +176,563 @@ fn update_popup(app: &mut App, key_event: KeyEvent, popup: Popup) {
             KeyCode::Backspace => {
                 app.popup_buffer.pop();
             }
-            KeyCode::BackTab => {
-                if app.popup_buffer.currently_selected > 0 {
-                    app.popup_buffer.currently_selected -= 1;
+            KeyCode::BackTab if app.popup_buffer.currently_selected > 0 => {
+                app.popup_buffer.currently_selected -= 1;
+            }
+            _ => {}
+        },
+        Popup::Config(true) => match key_event.code {
+            KeyCode::Esc => app.popup = None,
+            KeyCode::Backspace => app.config_buffer.pop(),
+            KeyCode::Char(c) => app.config_buffer.push(c),
+            KeyCode::Enter => {
+                let new_limit = &app.config_buffer.word_limit;
+                if new_limit.trim().is_empty() {
+                    app.config.chars_per_line = None;
+                } else {
+                    match new_limit.parse::<usize>() {
+                        Ok(limit) => app.config.chars_per_line = Some(limit),
+                        Err(_) => app.config_buffer.clear(),
+                    }
                 }
+                app.config_buffer.word_limit = if let Some(limit) = app.config.chars_per_line {
+                    limit.to_string()
+                } else {
+                    String::new()
+                };
+                confy::store("englog", None, &app.config).expect("Failed to save config");
+                app.popup = None;
             }
             _ => {}
         },
-        Popup::ConfDeleteDay => {
-            match key_event.code {
-                KeyCode::Char('y') | KeyCode::Char('Y') => {
-                    app.remove_day();
+        Popup::Metadata(selected, true) => {
+            let field = match app.config.fields.get(selected) {
+                Some(field) => field.clone(),
+                None => {
+                    app.popup = None;
+                    return;
                 }
-                _ => {}
             };
-            app.popup = None;
-        }
-        Popup::Config(editing) => {
-            if editing {
-                match key_event.code {
-                    KeyCode::Esc => app.popup = None,
-                    KeyCode::Backspace => app.config_buffer.pop(),
-                    KeyCode::Char(c) => app.config_buffer.push(c),
-                    KeyCode::Enter => {
-                        let new_limit = &app.config_buffer.word_limit;
-                        if new_limit.trim().is_empty() {
-                            app.config.chars_per_line = None;
-                        } else {
-                            match new_limit.parse::<usize>() {
-                                Ok(limit) => app.config.chars_per_line = Some(limit),
-                                Err(_) => app.config_buffer.clear(),
+            match key_event.code {
+                KeyCode::Esc => {
+                    app.meta_buffer.clear();
+                    app.popup = Some(Popup::Metadata(selected, false));
+                }
+                KeyCode::Backspace => {
+                    app.meta_buffer.pop();
+                }
+                KeyCode::Char(c) => app.meta_buffer.push(c),
+                KeyCode::Enter => {
+                    let input = app.meta_buffer.clone();
+                    if input.trim().is_empty() {
+                        app.set_selected_meta(&field.name, None);
+                    } else {
+                        match field.parse(&input) {
+                            Some(value) => app.set_selected_meta(&field.name, Some(value)),
+                            None => {
+                                app.meta_buffer.clear();
+                                return;
                             }
                         }
-                        app.config_buffer.word_limit =
-                            if let Some(limit) = app.config.chars_per_line {
-                                limit.to_string()
-                            } else {
-                                String::new()
-                            };
-                        confy::store("englog", None, &app.config).expect("Failed to save config");
-                        app.popup = None;
                     }
-                    _ => {}
+                    app.meta_buffer.clear();
+                    app.popup = Some(Popup::Metadata(selected, false));
                 }
-            } else {
-                match key_event.code {
-                    KeyCode::Char('e') => {
-                        app.popup = Some(Popup::Config(true));
+                _ => {}
+            }
+        }
+        Popup::SavedSearches(selected, true) => match key_event.code {
+            KeyCode::Esc => {
+                app.search_name.clear();
+                app.popup = Some(Popup::SavedSearches(selected, false));
+            }
+            KeyCode::Backspace => {
+                app.search_name.pop();
+            }
+            KeyCode::Char(c) => app.search_name.push(c),
+            KeyCode::Enter => {
+                let name = std::mem::take(&mut app.search_name);
+                app.save_current_search(&name);
+                let saved = app
+                    .config
+                    .searches
+                    .iter()
+                    .position(|s| s.name == name.trim())
+                    .unwrap_or(selected);
+                app.popup = Some(Popup::SavedSearches(saved, false));
+            }
+            _ => {}
+        },
+        Popup::Replace => {
+            let buffer = &mut app.replace_buffer;
+            match key_event.code {
+                KeyCode::Esc => app.popup = None,
+                KeyCode::Enter => {
+                    let has_changes = app.preview_replace();
+                    if has_changes {
+                        app.popup = Some(Popup::ReplacePreview(0));
+                    }
+                }
+                KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    buffer.regex = !buffer.regex;
+                    buffer.error = None;
+                }
+                KeyCode::Char('f') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    buffer.filtered_only = !buffer.filtered_only;
+                    buffer.error = None;
+                }
+                KeyCode::Char(char) => buffer.push(char),
+                KeyCode::Backspace => buffer.pop(),
+                KeyCode::Tab | KeyCode::Down => {
+                    buffer.currently_selected = (buffer.currently_selected + 1) % 3;
+                }
+                KeyCode::BackTab | KeyCode::Up => {
+                    buffer.currently_selected = (buffer.currently_selected + 2) % 3;
+                }
+                _ => {}
+            }
+        }
+        Popup::Palette(selected) => {
+            let count = app.palette_commands().len();
+            let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+            match key_event.code {
+                KeyCode::Esc => {
+                    app.palette_query.clear();
+                    app.popup = None;
+                }
+                KeyCode::Enter => {
+                    let command = app.palette_commands().into_iter().nth(selected);
+                    app.palette_query.clear();
+                    app.popup = None;
+                    if let Some(command) = command {
+                        switch_context(app, command.context, tx);
+                        screen_action(app, command.action, tx);
                     }
-                    _ => app.popup = None,
                 }
+                KeyCode::Up | KeyCode::BackTab => {
+                    app.popup = Some(Popup::Palette(selected.saturating_sub(1)))
+                }
+                KeyCode::Char('p') if ctrl => {
+                    app.popup = Some(Popup::Palette(selected.saturating_sub(1)))
+                }
+                KeyCode::Down | KeyCode::Tab if selected + 1 < count => {
+                    app.popup = Some(Popup::Palette(selected + 1))
+                }
+                KeyCode::Char('n') if ctrl && selected + 1 < count => {
+                    app.popup = Some(Popup::Palette(selected + 1))
+                }
+                KeyCode::Char(c) if !ctrl => {
+                    app.palette_query.push(c);
+                    app.popup = Some(Popup::Palette(0));
+                }
+                KeyCode::Backspace => {
+                    app.palette_query.pop();
+                    app.popup = Some(Popup::Palette(0));
+                }
+                _ => {}
+            }
+        }
+        // Saved searches are numbered, so digits apply them whatever the bindings are
+        Popup::SavedSearches(_, false) if matches!(key_event.code, KeyCode::Char('1'..='9')) => {
+            if let KeyCode::Char(c) = key_event.code {
+                app.apply_saved_search(c as usize - '1' as usize);
+            }
+            app.popup = None;
+        }
+        // Everything else is a list or a question, so it goes through the popup bindings
+        _ => match next_action(app, Context::Popup, key_event).1 {
+            Lookup::Action(action) => popup_action(app, popup, Some(action)),
+            Lookup::Pending => {}
+            Lookup::Unbound => popup_action(app, popup, None),
+        },
+    }
+}
+
+// `None` is a key with no binding, which is enough to dismiss the simpler popups
+fn popup_action(app: &mut App, popup: Popup, action: Option<Action>) {
+    match popup {
+        Popup::ConfDeleteDay => {
+            if action == Some(Action::Confirm) {
+                app.remove_day();
+            }
+            app.popup = None;
+        }
+        Popup::ConfCreateDay(date) => {
+            app.popup = None;
+            if action == Some(Action::Confirm) {
+                app.open_link(Link {
+                    date,
+                    heading: None,
+                });
+            }
+        }
+        Popup::Config(_) => {
+            app.popup = match action {
+                Some(Action::Edit) => Some(Popup::Config(true)),
+                _ => None,
             }
         }
         Popup::Info(_) => app.popup = None,
+        Popup::ProjectPicker(selected) => match action {
+            Some(Action::Back) => app.popup = None,
+            Some(Action::MoveUp) if selected > 0 => {
+                app.popup = Some(Popup::ProjectPicker(selected - 1));
+            }
+            Some(Action::MoveDown) if selected + 1 < app.config.projects.len() => {
+                app.popup = Some(Popup::ProjectPicker(selected + 1));
+            }
+            Some(Action::Select) => {
+                if let Some(project) = app.config.projects.get(selected).cloned() {
+                    app.assign_project_to_selected(&project);
+                }
+                app.popup = None;
+            }
+            _ => {}
+        },
+        Popup::Metadata(selected, _) => {
+            let field = match app.config.fields.get(selected) {
+                Some(field) => field.clone(),
+                None => {
+                    app.popup = None;
+                    return;
+                }
+            };
+            match action {
+                Some(Action::Back) => app.popup = None,
+                Some(Action::MoveUp) if selected > 0 => {
+                    app.popup = Some(Popup::Metadata(selected - 1, false));
+                }
+                Some(Action::MoveDown) if selected + 1 < app.config.fields.len() => {
+                    app.popup = Some(Popup::Metadata(selected + 1, false));
+                }
+                Some(Action::Select | Action::Edit) => match field.kind {
+                    FieldKind::Bool | FieldKind::Enum => {
+                        let current = app.selected_day().and_then(|d| d.meta_value(&field.name));
+                        let next = field.cycle(current);
+                        app.set_selected_meta(&field.name, next);
+                    }
+                    FieldKind::Number | FieldKind::Text => {
+                        app.meta_buffer = app
+                            .selected_day()
+                            .and_then(|d| d.meta_value(&field.name))
+                            .map(|v| v.to_string())
+                            .unwrap_or_default();
+                        app.popup = Some(Popup::Metadata(selected, true));
+                    }
+                },
+                Some(Action::Delete) => app.set_selected_meta(&field.name, None),
+                _ => {}
+            }
+        }
+        Popup::SavedSearches(selected, _) => match action {
+            Some(Action::Back) => app.popup = None,
+            Some(Action::MoveUp) if selected > 0 => {
+                app.popup = Some(Popup::SavedSearches(selected - 1, false));
+            }
+            Some(Action::MoveDown) if selected + 1 < app.config.searches.len() => {
+                app.popup = Some(Popup::SavedSearches(selected + 1, false));
+            }
+            Some(Action::Select) => {
+                app.apply_saved_search(selected);
+                app.popup = None;
+            }
+            Some(Action::Add) if app.filter.is_some() && !app.fuzzy => {
+                app.popup = Some(Popup::SavedSearches(selected, true));
+            }
+            Some(Action::Delete) => {
+                app.delete_saved_search(selected);
+                let last = app.config.searches.len().saturating_sub(1);
+                app.popup = Some(Popup::SavedSearches(selected.min(last), false));
+            }
+            _ => {}
+        },
+        Popup::ReplacePreview(scroll) => match action {
+            Some(Action::Back | Action::Deny) => {
+                app.replace_changes.clear();
+                app.popup = Some(Popup::Replace);
+            }
+            Some(Action::Select | Action::Confirm) => {
+                app.apply_replace();
+                app.popup = None;
+            }
+            Some(Action::MoveDown) => {
+                app.popup = Some(Popup::ReplacePreview(scroll.saturating_add(1)))
+            }
+            Some(Action::MoveUp) => {
+                app.popup = Some(Popup::ReplacePreview(scroll.saturating_sub(1)))
+            }
+            _ => {}
+        },
+        // These take text, so their keys are handled in `update_popup`
+        Popup::NewDay | Popup::Replace | Popup::Palette(_) => {}
+    }
+}
+
+// Back to the day list from either mode, saving on the way as nothing else does before quitting
+fn leave_day(app: &mut App, tx: &UnboundedSender<Event>) {
+    app.update_day_from_buffer();
+    app.current_screen = CurrentScreen::Main(false);
+    //TODO remove this useless testing stuff and use Tokio
+    let sender = tx.clone();
+    sender.send(Event::Loading(Loading::Saving(true))).unwrap();
+    app.save();
+    sender.send(Event::Loading(Loading::Saving(false))).unwrap();
+    app.refresh_people();
+    app.link_history.clear();
+}
+
+fn update_screen(app: &mut App, key_event: KeyEvent, tx: &UnboundedSender<Event>) {
+    let context = app.key_context();
+    if context == Context::Day && app.pending_keys.is_empty() && app.vim_input(key_event.into()) {
+        return;
+    }
+    let (stranded, lookup) = next_action(app, context, key_event);
+    for key_event in stranded {
+        unbound_key(app, context, key_event);
+    }
+    match lookup {
+        // Tab only completes when there's something to complete
+        Lookup::Action(Action::Complete) if app.mention_completions().is_empty() => {
+            if app.inserting() {
+                app.input_to_current_day(key_event.into())
+            }
+        }
+        Lookup::Action(action) => screen_action(app, action, tx),
+        Lookup::Pending => {}
+        Lookup::Unbound => unbound_key(app, context, key_event),
+    }
+}
+
+// Keys with no binding are typed when there's somewhere to type them
+fn unbound_key(app: &mut App, context: Context, key_event: KeyEvent) {
+    match context {
+        Context::Filter => app.input_to_filter_buffer(key_event.into()),
+        Context::Day if app.inserting() => app.input_to_current_day(key_event.into()),
+        Context::Main => {
+            if let KeyCode::Char(c @ '1'..='9') = key_event.code {
+                app.apply_saved_search(c as usize - '1' as usize)
+            }
+        }
+        _ => {}
+    }
+}
+
+// Opens the screen a palette command belongs to, the same way its own key would
+fn switch_context(app: &mut App, context: Context, tx: &UnboundedSender<Event>) {
+    if context == app.screen_context() {
+        return;
+    }
+    match (&app.current_screen, context) {
+        (CurrentScreen::ViewingDay, Context::Day | Context::Read) => {
+            return screen_action(app, Action::ReadMode, tx);
+        }
+        (CurrentScreen::ViewingDay, _) => leave_day(app, tx),
+        _ => app.current_screen = CurrentScreen::Main(false),
     }
+    let open = match context {
+        Context::Filter => Action::Filter,
+        Context::Day | Context::Read => {
+            app.read_mode = context == Context::Read;
+            app.read_scroll = 0;
+            Action::Open
+        }
+        Context::Projects => Action::Projects,
+        Context::People => Action::People,
+        Context::Calendar => Action::Calendar,
+        Context::Heatmap => Action::Heatmap,
+        Context::SearchResults => Action::MatchingLines,
+        Context::Stats => Action::Stats,
+        Context::Main | Context::Popup => return,
+    };
+    screen_action(app, open, tx);
 }
 
-fn update_screen(app: &mut App, key_event: KeyEvent, rx: &UnboundedSender<Event>) {
+fn screen_action(app: &mut App, action: Action, tx: &UnboundedSender<Event>) {
+    if action == Action::Palette {
+        app.palette_query.clear();
+        app.popup = Some(Popup::Palette(0));
+        return;
+    }
     match app.current_screen {
-        CurrentScreen::Main(true) => match key_event.into() {
-            Input {
-                key: Key::Enter, ..
-            } => {
+        CurrentScreen::Main(true) => match action {
+            Action::Done => {
                 app.current_screen = CurrentScreen::Main(false);
                 let count = app.filtered_days().count();
                 if app.currently_selected >= count && count > 0 {
                     app.currently_selected = count - 1;
                 }
             }
-            Input { key: Key::Esc, .. } => {
-                app.filter = None;
-                app.current_screen = CurrentScreen::Main(false);
-            }
-            input => app.input_to_filter_buffer(input),
+            Action::ClearFilter => app.remove_filter(),
+            Action::ToggleFuzzy => app.toggle_fuzzy(),
+            _ => {}
         },
-        CurrentScreen::Main(false) => match key_event.code {
-            KeyCode::Char('r') => app.switch_to_current_day(),
-            KeyCode::Esc => app.filter = None,
-            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => {
+        CurrentScreen::Main(false) => match action {
+            Action::Today => app.switch_to_current_day(),
+            Action::ClearFilter => app.set_filter(None),
+            Action::Open => {
                 app.load_text();
                 app.current_screen = CurrentScreen::ViewingDay;
             }
-            KeyCode::Up | KeyCode::Char('k') => app.decrement_selected(),
-            KeyCode::Down | KeyCode::Char('j') => app.increment_selected(),
-            KeyCode::Char('u') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+            Action::MoveUp => app.decrement_selected(),
+            Action::MoveDown => app.increment_selected(),
+            Action::PageUp => {
                 if app.currently_selected < 10 {
                     app.currently_selected = 0;
                 } else {
                     app.currently_selected -= 10;
                 }
             }
-            KeyCode::Char('d') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
-                let max_index = app.filtered_days().count() - 1;
-                if max_index - app.currently_selected < 10 {
-                    app.currently_selected = max_index;
-                } else {
-                    app.currently_selected += 10;
-                }
+            Action::PageDown => {
+                let max_index = app.filtered.len().saturating_sub(1);
+                app.currently_selected = (app.currently_selected + 10).min(max_index);
+            }
+            Action::DeleteDay => app.popup = Some(Popup::ConfDeleteDay),
+            Action::Info => app.popup = Some(Popup::Info(Info::About)),
+            Action::Config => app.popup = Some(Popup::Config(false)),
+            Action::NewDay => app.popup = Some(Popup::NewDay),
+            Action::AssignProject if !app.config.projects.is_empty() => {
+                app.popup = Some(Popup::ProjectPicker(0))
+            }
+            Action::MatchingLines if app.query.is_some() => app.show_search_results(),
+            Action::Metadata if !app.config.fields.is_empty() => {
+                app.popup = Some(Popup::Metadata(0, false))
+            }
+            Action::SavedSearches => app.popup = Some(Popup::SavedSearches(0, false)),
+            Action::Replace => app.popup = Some(Popup::Replace),
+            Action::Calendar => app.show_calendar(),
+            Action::Heatmap => app.show_heatmap(),
+            Action::Stats => app.show_stats(),
+            Action::UndoReplace if !app.replace_undo.is_empty() => app.undo_replace(),
+            Action::People => {
+                app.refresh_people();
+                app.people_scroll = 0;
+                app.current_screen = CurrentScreen::People;
+            }
+            Action::Projects => {
+                app.project_scroll = 0;
+                app.current_screen = CurrentScreen::Projects;
             }
-            KeyCode::Char('d') => app.popup = Some(Popup::ConfDeleteDay),
-            KeyCode::Char('i') => app.popup = Some(Popup::Info(Info::About)),
-            KeyCode::Char('c') => app.popup = Some(Popup::Config(false)),
-            KeyCode::Char('n') => app.popup = Some(Popup::NewDay),
-            KeyCode::Char('q') => app.should_quit = true,
-            KeyCode::Char('b') => app.currently_selected = app.days.days.len() - 1,
-            KeyCode::Char(':') => {
+            Action::Quit => app.should_quit = true,
+            Action::Top => app.currently_selected = 0,
+            Action::Bottom => app.currently_selected = app.filtered.len().saturating_sub(1),
+            Action::Filter => {
                 app.current_screen = CurrentScreen::Main(true);
                 app.init_filter_text();
             }
             _ => {}
         },
-        CurrentScreen::ViewingDay => {
-            match key_event.into() {
-                Input { key: Key::Esc, .. } => {
-                    app.update_day_from_buffer();
-                    app.current_screen = CurrentScreen::Main(false);
-                    //TODO remove this useless testing stuff and use Tokio
-                    let sender = rx.clone();
-                    sender.send(Event::Loading(Loading::Saving(true))).unwrap();
-                    save_inner(&app.days, &app.file_path);
-                    sender.send(Event::Loading(Loading::Saving(false))).unwrap();
-                }
-                input => app.input_to_current_day(input),
-            };
+        CurrentScreen::ViewingDay if app.read_mode => match action {
+            Action::ReadMode => app.read_mode = false,
+            Action::Back => leave_day(app, tx),
+            Action::ScrollDown => app.read_scroll = app.read_scroll.saturating_add(1),
+            Action::ScrollUp => app.read_scroll = app.read_scroll.saturating_sub(1),
+            Action::PageDown => app.read_scroll = app.read_scroll.saturating_add(10),
+            Action::PageUp => app.read_scroll = app.read_scroll.saturating_sub(10),
+            _ => {}
+        },
+        CurrentScreen::ViewingDay => match action {
+            Action::Back => leave_day(app, tx),
+            Action::FollowLink => app.follow_link(),
+            Action::LinkBack => app.follow_link_back(),
+            Action::ReadMode => {
+                app.update_day_from_buffer();
+                app.read_scroll = 0;
+                app.read_mode = true;
+            }
+            Action::Complete => app.complete_mention(),
+            Action::Reflow => app.reflow_paragraph(),
+            Action::ReflowDay => app.reflow_day(),
+            Action::SoftWrap => app.config.soft_wrap = !app.config.soft_wrap,
+            _ => {}
+        },
+        CurrentScreen::Projects => match action {
+            Action::Back => app.current_screen = CurrentScreen::Main(false),
+            Action::MoveUp if app.project_selected > 0 => {
+                app.project_selected -= 1;
+                app.project_scroll = 0;
+            }
+            Action::MoveDown if app.project_selected + 1 < app.config.projects.len() => {
+                app.project_selected += 1;
+                app.project_scroll = 0;
+            }
+            Action::ScrollDown => app.project_scroll = app.project_scroll.saturating_add(1),
+            Action::ScrollUp => app.project_scroll = app.project_scroll.saturating_sub(1),
+            Action::PageDown => app.project_scroll = app.project_scroll.saturating_add(10),
+            Action::PageUp => app.project_scroll = app.project_scroll.saturating_sub(10),
+            _ => {}
+        },
+        CurrentScreen::People => {
+            let people_count = app.people.len();
+            match action {
+                Action::Back => app.current_screen = CurrentScreen::Main(false),
+                Action::MoveUp if app.person_selected > 0 => {
+                    app.person_selected -= 1;
+                    app.people_scroll = 0;
+                }
+                Action::MoveDown if app.person_selected + 1 < people_count => {
+                    app.person_selected += 1;
+                    app.people_scroll = 0;
+                }
+                Action::ScrollDown => app.people_scroll = app.people_scroll.saturating_add(1),
+                Action::ScrollUp => app.people_scroll = app.people_scroll.saturating_sub(1),
+                Action::PageDown => app.people_scroll = app.people_scroll.saturating_add(10),
+                Action::PageUp => app.people_scroll = app.people_scroll.saturating_sub(10),
+                _ => {}
+            }
+        }
+        CurrentScreen::Calendar => {
+            let date = app.calendar_date;
+            match action {
+                Action::Back => app.current_screen = CurrentScreen::Main(false),
+                Action::MoveLeft => app.calendar_date = date - Duration::days(1),
+                Action::MoveRight => app.calendar_date = date + Duration::days(1),
+                Action::MoveUp => app.calendar_date = date - Duration::days(7),
+                Action::MoveDown => app.calendar_date = date + Duration::days(7),
+                Action::Previous => app.calendar_date = shift_months(date, -1),
+                Action::Next => app.calendar_date = shift_months(date, 1),
+                Action::Today => app.calendar_date = chrono::Local::now().date_naive(),
+                Action::Open => app.open_date(date),
+                _ => {}
+            }
+        }
+        // Weeks run left to right and weekdays top to bottom, so left/right move by week
+        CurrentScreen::Heatmap => {
+            let date = app.calendar_date;
+            match action {
+                Action::Back => app.current_screen = CurrentScreen::Main(false),
+                Action::MoveLeft => app.calendar_date = date - Duration::days(7),
+                Action::MoveRight => app.calendar_date = date + Duration::days(7),
+                Action::MoveUp => app.calendar_date = date - Duration::days(1),
+                Action::MoveDown => app.calendar_date = date + Duration::days(1),
+                Action::Previous => app.calendar_date = shift_months(date, -12),
+                Action::Next => app.calendar_date = shift_months(date, 12),
+                Action::Today => app.calendar_date = chrono::Local::now().date_naive(),
+                Action::ToggleActivity => app.heatmap_activity = app.heatmap_activity.toggle(),
+                Action::Open => app.open_date(date),
+                _ => {}
+            }
+        }
+        CurrentScreen::SearchResults => {
+            let count = app.search_results.len();
+            let selected = app.results_state.selected().unwrap_or(0);
+            match action {
+                Action::Back => app.current_screen = CurrentScreen::Main(false),
+                Action::Open => app.open_search_result(),
+                Action::MoveUp if selected > 0 => app.results_state.select(Some(selected - 1)),
+                Action::MoveDown if selected + 1 < count => {
+                    app.results_state.select(Some(selected + 1))
+                }
+                Action::PageUp => app.results_state.select(Some(selected.saturating_sub(10))),
+                Action::PageDown if count > 0 => app
+                    .results_state
+                    .select(Some((selected + 10).min(count - 1))),
+                _ => {}
+            }
+        }
+        CurrentScreen::Stats => {
+            if action == Action::Back {
+                app.current_screen = CurrentScreen::Main(false);
+            }
         }
     }
 }
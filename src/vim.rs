@@ -0,0 +1,1045 @@
+use tui_textarea::{CursorMove, Input, Key, TextArea};
+
+use crate::app::App;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+    VisualLine,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Normal => "NORMAL",
+            Mode::Insert => "INSERT",
+            Mode::Visual => "VISUAL",
+            Mode::VisualLine => "VISUAL LINE",
+        }
+    }
+}
+
+// Keys that mean nothing until the next one arrives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Waiting {
+    // `g`, for `gg`
+    G,
+    // `f`, `F`, `t` or `T`, for the character to look for
+    Find(char),
+    // `r`, for the replacement character
+    Replace,
+    // `i` or `a` after an operator, for the kind of text object. True for `i`
+    Object(bool),
+}
+
+// How much text an operator takes when it's given a motion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reach {
+    // Up to the target, like `w` and `h`
+    Exclusive,
+    // Up to and including the character at the target, like `e` and `f`
+    Inclusive,
+    // Every line from the cursor's to the target's, like `j` and `G`
+    Lines,
+}
+
+type Position = (usize, usize);
+
+#[derive(Debug, Default)]
+pub struct Vim {
+    pub mode: Mode,
+    count: Option<usize>,
+    // Operator waiting for a motion, and the count typed before it
+    operator: Option<char>,
+    operator_count: Option<usize>,
+    waiting: Option<Waiting>,
+    // The command typed so far, e.g. "2d", shown next to the mode
+    pub typed: String,
+    // Whether the last yank or delete took whole lines, so `p` puts it on lines of its own
+    linewise: bool,
+    // Where the visual selection started
+    anchor: Position,
+}
+
+impl Vim {
+    // Returns whether the key was used. Insert mode only takes esc, everything else
+    // is typed as usual
+    pub fn input(&mut self, input: Input, textarea: &mut TextArea) -> bool {
+        if self.mode == Mode::Insert {
+            if input.key != Key::Esc {
+                return false;
+            }
+            self.mode = Mode::Normal;
+            if textarea.cursor().1 > 0 {
+                textarea.move_cursor(CursorMove::Back);
+            }
+            return true;
+        }
+        if input.key == Key::Esc {
+            let pending = !self.typed.is_empty();
+            self.reset();
+            if self.mode == Mode::Normal {
+                return pending;
+            }
+            textarea.cancel_selection();
+            self.mode = Mode::Normal;
+            clamp(textarea);
+            return true;
+        }
+        if input.ctrl || input.alt {
+            let used = input.ctrl && input.key == Key::Char('r');
+            if used {
+                for _ in 0..self.take_count() {
+                    textarea.redo();
+                }
+            }
+            self.reset();
+            return used;
+        }
+        let key = input.key;
+        if let Key::Char(c) = key {
+            self.typed.push(c);
+        }
+        self.key(key, textarea);
+        if self.waiting.is_none() && self.operator.is_none() && self.count.is_none() {
+            self.typed.clear();
+        }
+        if self.mode == Mode::Normal {
+            clamp(textarea);
+        }
+        // Anything else that isn't a plain character, like tab or f1, belongs to the bindings
+        matches!(
+            key,
+            Key::Char(_)
+                | Key::Left
+                | Key::Right
+                | Key::Up
+                | Key::Down
+                | Key::Home
+                | Key::End
+                | Key::Backspace
+                | Key::Enter
+        )
+    }
+
+    fn reset(&mut self) {
+        self.count = None;
+        self.operator = None;
+        self.operator_count = None;
+        self.waiting = None;
+        self.typed.clear();
+    }
+
+    // Everything typed in front of the command, so `2d3w` deletes six words
+    fn total_count(&self) -> Option<usize> {
+        match (self.operator_count, self.count) {
+            (Some(before), Some(after)) => Some(before.saturating_mul(after)),
+            (before, after) => before.or(after),
+        }
+    }
+
+    fn take_count(&mut self) -> usize {
+        let count = self.total_count().unwrap_or(1);
+        self.reset();
+        count
+    }
+
+    fn key(&mut self, key: Key, textarea: &mut TextArea) {
+        if let Some(waiting) = self.waiting.take() {
+            let Key::Char(c) = key else {
+                return self.reset();
+            };
+            match waiting {
+                Waiting::G if c == 'g' => {
+                    let row = self.total_count().map_or(0, |count| count - 1);
+                    let row = row.min(textarea.lines().len() - 1);
+                    let target = first_non_blank(textarea.lines(), row);
+                    self.motion(target, Reach::Lines, textarea);
+                }
+                Waiting::Find(kind) => {
+                    let count = self.total_count().unwrap_or(1);
+                    if let Some((target, reach)) = find(textarea, kind, c, count) {
+                        self.motion(target, reach, textarea);
+                    } else {
+                        self.reset();
+                    }
+                }
+                Waiting::Replace => {
+                    let count = self.take_count();
+                    replace(textarea, c, count);
+                }
+                Waiting::Object(inner) => {
+                    let text = Text::new(textarea.lines());
+                    let cursor = text.index(textarea.cursor());
+                    match (self.operator, object(&text, cursor, c, inner)) {
+                        (Some(op), Some((start, end))) => {
+                            self.reset();
+                            self.operate_range(op, start, end, &text, textarea);
+                        }
+                        _ => self.reset(),
+                    }
+                }
+                _ => self.reset(),
+            }
+            return;
+        }
+
+        let c = match key {
+            Key::Char(c) => c,
+            Key::Left | Key::Backspace => 'h',
+            Key::Right => 'l',
+            Key::Up => 'k',
+            Key::Down | Key::Enter => 'j',
+            Key::Home => '0',
+            Key::End => '$',
+            _ => return self.reset(),
+        };
+        match c {
+            '1'..='9' => self.push_digit(c),
+            '0' if self.count.is_some() => self.push_digit(c),
+            'g' => self.waiting = Some(Waiting::G),
+            'f' | 'F' | 't' | 'T' => self.waiting = Some(Waiting::Find(c)),
+            'i' | 'a' if self.operator.is_some() => self.waiting = Some(Waiting::Object(c == 'i')),
+            _ if self.mode == Mode::Normal => self.normal(c, textarea),
+            _ => self.visual(c, textarea),
+        }
+    }
+
+    fn push_digit(&mut self, digit: char) {
+        let digit = digit as usize - '0' as usize;
+        self.count = Some(
+            self.count
+                .unwrap_or(0)
+                .saturating_mul(10)
+                .saturating_add(digit),
+        );
+    }
+
+    fn normal(&mut self, c: char, textarea: &mut TextArea) {
+        let (row, col) = textarea.cursor();
+        // `dd`, `yy` and `cc` take whole lines, counting down from the cursor's
+        if let Some(op) = self.operator {
+            if op == c {
+                let count = self.take_count();
+                let last = row
+                    .saturating_add(count - 1)
+                    .min(textarea.lines().len() - 1);
+                return self.operate_lines(op, row, last, textarea);
+            }
+        }
+        if let Some((target, reach)) = self.target(c, textarea) {
+            return self.motion(target, reach, textarea);
+        }
+        if self.operator.is_some() {
+            return self.reset();
+        }
+        let line_len = textarea.lines()[row].chars().count();
+        match c {
+            'd' | 'y' | 'c' => {
+                self.operator = Some(c);
+                self.operator_count = self.count.take();
+            }
+            // Shorthands for an operator and a motion
+            'x' | 'X' | 'D' | 'C' | 's' | 'S' | 'Y' => {
+                let (op, motion) = match c {
+                    'x' => ('d', 'l'),
+                    'X' => ('d', 'h'),
+                    'D' => ('d', '$'),
+                    'C' => ('c', '$'),
+                    's' => ('c', 'l'),
+                    'S' => ('c', 'c'),
+                    _ => ('y', 'y'),
+                };
+                if matches!(c, 'x' | 'X' | 'D') && line_len == 0 {
+                    return self.reset();
+                }
+                self.operator = Some(op);
+                self.operator_count = self.count.take();
+                self.normal(motion, textarea);
+            }
+            'p' | 'P' => {
+                let count = self.take_count();
+                self.paste(c == 'p', count, textarea);
+            }
+            'u' => {
+                for _ in 0..self.take_count() {
+                    textarea.undo();
+                }
+            }
+            'J' => {
+                let count = self.take_count().max(2);
+                join(textarea, count - 1);
+            }
+            'r' => self.waiting = Some(Waiting::Replace),
+            'i' | 'a' | 'I' | 'A' | 'o' | 'O' => {
+                self.reset();
+                match c {
+                    'a' if col < line_len => textarea.move_cursor(CursorMove::Forward),
+                    'I' => jump(textarea, first_non_blank(textarea.lines(), row)),
+                    'A' => textarea.move_cursor(CursorMove::End),
+                    'o' => {
+                        textarea.move_cursor(CursorMove::End);
+                        textarea.insert_newline();
+                    }
+                    'O' => {
+                        textarea.move_cursor(CursorMove::Head);
+                        textarea.insert_newline();
+                        textarea.move_cursor(CursorMove::Up);
+                    }
+                    _ => {}
+                }
+                self.mode = Mode::Insert;
+            }
+            'v' | 'V' => {
+                self.reset();
+                self.anchor = textarea.cursor();
+                self.mode = if c == 'v' {
+                    Mode::Visual
+                } else {
+                    Mode::VisualLine
+                };
+                self.select(textarea);
+            }
+            _ => self.reset(),
+        }
+    }
+
+    fn visual(&mut self, c: char, textarea: &mut TextArea) {
+        if let Some((target, reach)) = self.target(c, textarea) {
+            return self.motion(target, reach, textarea);
+        }
+        self.reset();
+        let cursor = textarea.cursor();
+        match c {
+            'v' | 'V' => {
+                let mode = if c == 'v' {
+                    Mode::Visual
+                } else {
+                    Mode::VisualLine
+                };
+                if mode == self.mode {
+                    textarea.cancel_selection();
+                    self.mode = Mode::Normal;
+                    clamp(textarea);
+                } else {
+                    self.mode = mode;
+                    self.select(textarea);
+                }
+            }
+            // Swap which end of the selection the cursor is at
+            'o' => {
+                let anchor = self.anchor;
+                self.anchor = cursor;
+                textarea.cancel_selection();
+                jump(textarea, anchor);
+                self.select(textarea);
+            }
+            'd' | 'x' | 'y' | 'c' | 's' | 'D' | 'X' | 'Y' | 'C' | 'S' => {
+                let op = match c {
+                    'd' | 'x' | 'D' | 'X' => 'd',
+                    'y' | 'Y' => 'y',
+                    _ => 'c',
+                };
+                let lines = self.mode == Mode::VisualLine || c.is_ascii_uppercase();
+                textarea.cancel_selection();
+                self.mode = Mode::Normal;
+                if lines {
+                    self.operate_lines(
+                        op,
+                        self.anchor.0.min(cursor.0),
+                        self.anchor.0.max(cursor.0),
+                        textarea,
+                    );
+                } else {
+                    let text = Text::new(textarea.lines());
+                    let (start, end) = ordered(text.index(self.anchor), text.index(cursor));
+                    let end = text.past(end);
+                    self.operate_range(op, start, end, &text, textarea);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Shows the selection. Visual line mode covers the whole of every line in it
+    fn select(&self, textarea: &mut TextArea) {
+        let cursor = textarea.cursor();
+        textarea.cancel_selection();
+        match self.mode {
+            Mode::VisualLine if cursor.0 >= self.anchor.0 => {
+                jump(textarea, (self.anchor.0, 0));
+                textarea.start_selection();
+                jump(textarea, (cursor.0, usize::MAX));
+            }
+            Mode::VisualLine => {
+                jump(textarea, (self.anchor.0, usize::MAX));
+                textarea.start_selection();
+                jump(textarea, (cursor.0, 0));
+            }
+            _ => {
+                jump(textarea, self.anchor);
+                textarea.start_selection();
+                jump(textarea, cursor);
+            }
+        }
+    }
+
+    // What visual mode has selected, from the first position up to but not including the last
+    pub fn selection(&self, cursor: Position) -> Option<(Position, Position)> {
+        let (start, end) = (self.anchor.min(cursor), self.anchor.max(cursor));
+        match self.mode {
+            Mode::Visual => Some((start, (end.0, end.1 + 1))),
+            Mode::VisualLine => Some(((start.0, 0), (end.0, usize::MAX))),
+            _ => None,
+        }
+    }
+
+    // Where a motion key lands, or None if the key isn't a motion
+    fn target(&self, c: char, textarea: &TextArea) -> Option<(Position, Reach)> {
+        let lines = textarea.lines();
+        let (row, col) = textarea.cursor();
+        let last_row = lines.len() - 1;
+        let line_len = lines[row].chars().count();
+        let count = self.total_count().unwrap_or(1);
+        let text = Text::new(lines);
+        let cursor = text.index((row, col));
+        let words = |step: fn(&Text, usize, bool) -> usize, big: bool| {
+            (0..count).fold(cursor, |index, _| step(&text, index, big))
+        };
+        let target = match c {
+            'h' => ((row, col.saturating_sub(count)), Reach::Exclusive),
+            'l' | ' ' => (
+                (row, col.saturating_add(count).min(line_len)),
+                Reach::Exclusive,
+            ),
+            'j' => ((row.saturating_add(count).min(last_row), col), Reach::Lines),
+            'k' => ((row.saturating_sub(count), col), Reach::Lines),
+            '0' => ((row, 0), Reach::Exclusive),
+            '^' => (first_non_blank(lines, row), Reach::Exclusive),
+            '$' => {
+                let row = row.saturating_add(count - 1).min(last_row);
+                (
+                    (row, lines[row].chars().count().saturating_sub(1)),
+                    Reach::Inclusive,
+                )
+            }
+            'G' => {
+                let row = self.total_count().map_or(last_row, |count| count - 1);
+                (first_non_blank(lines, row.min(last_row)), Reach::Lines)
+            }
+            'w' | 'W' => {
+                let mut target = text.position(words(word_forward, c == 'W'));
+                // An operator stops at the end of the line rather than taking the line break
+                if self.operator.is_some()
+                    && target.0 > row
+                    && target.1 <= first_non_blank(lines, target.0).1
+                {
+                    let end_row = target.0 - 1;
+                    target = (end_row, lines[end_row].chars().count());
+                }
+                // `cw` changes the word without the space after it, like `ce`
+                if self.operator == Some('c')
+                    && !text.chars.get(cursor).is_some_and(|c| c.is_whitespace())
+                {
+                    (text.position(words(word_end, c == 'W')), Reach::Inclusive)
+                } else {
+                    (target, Reach::Exclusive)
+                }
+            }
+            'b' | 'B' => (text.position(words(word_back, c == 'B')), Reach::Exclusive),
+            'e' | 'E' => (text.position(words(word_end, c == 'E')), Reach::Inclusive),
+            '}' => {
+                let mut target = row;
+                for _ in 0..count {
+                    target = (target + 1..=last_row)
+                        .find(|row| lines[*row].trim().is_empty())
+                        .unwrap_or(last_row);
+                }
+                ((target, usize::MAX), Reach::Exclusive)
+            }
+            '{' => {
+                let mut target = row;
+                for _ in 0..count {
+                    target = (0..target)
+                        .rev()
+                        .find(|row| lines[*row].trim().is_empty())
+                        .unwrap_or(0);
+                }
+                ((target, 0), Reach::Exclusive)
+            }
+            _ => return None,
+        };
+        Some(target)
+    }
+
+    // Moves to a target, or hands it to the operator waiting for one
+    fn motion(&mut self, target: Position, reach: Reach, textarea: &mut TextArea) {
+        let operator = self.operator;
+        self.reset();
+        let Some(op) = operator else {
+            jump(textarea, target);
+            if self.mode == Mode::VisualLine {
+                self.select(textarea);
+            }
+            return;
+        };
+        let cursor = textarea.cursor();
+        if reach == Reach::Lines {
+            return self.operate_lines(
+                op,
+                cursor.0.min(target.0),
+                cursor.0.max(target.0),
+                textarea,
+            );
+        }
+        let text = Text::new(textarea.lines());
+        let (start, end) = ordered(text.index(cursor), text.index(target));
+        let end = if reach == Reach::Inclusive {
+            text.past(end)
+        } else {
+            end
+        };
+        self.operate_range(op, start, end, &text, textarea);
+    }
+
+    // Yanks, deletes or changes the characters from `start` up to `end`
+    fn operate_range(
+        &mut self,
+        op: char,
+        start: usize,
+        end: usize,
+        text: &Text,
+        textarea: &mut TextArea,
+    ) {
+        if op == 'c' {
+            self.mode = Mode::Insert;
+        }
+        if start >= end {
+            return;
+        }
+        let start = text.position(start);
+        jump(textarea, start);
+        textarea.start_selection();
+        jump(textarea, text.position(end));
+        if op == 'y' {
+            textarea.copy();
+            jump(textarea, start);
+        } else {
+            textarea.cut();
+        }
+        self.linewise = false;
+    }
+
+    fn operate_lines(&mut self, op: char, first: usize, last: usize, textarea: &mut TextArea) {
+        let lines = textarea.lines();
+        let yanked = lines[first..=last].join("\n");
+        let line_count = lines.len();
+        let col = textarea.cursor().1;
+        match op {
+            'y' => jump(textarea, (first, col)),
+            'd' => {
+                // Take the line break before the lines when there's nothing after them
+                let (start, end) = if last + 1 < line_count {
+                    ((first, 0), (last + 1, 0))
+                } else if first > 0 {
+                    ((first - 1, usize::MAX), (last, usize::MAX))
+                } else {
+                    ((0, 0), (last, usize::MAX))
+                };
+                jump(textarea, start);
+                textarea.start_selection();
+                jump(textarea, end);
+                textarea.cut();
+                let row = first.min(textarea.lines().len() - 1);
+                jump(textarea, first_non_blank(textarea.lines(), row));
+            }
+            _ => {
+                jump(textarea, (first, 0));
+                textarea.start_selection();
+                jump(textarea, (last, usize::MAX));
+                textarea.cut();
+                self.mode = Mode::Insert;
+            }
+        }
+        textarea.set_yank_text(yanked);
+        self.linewise = true;
+    }
+
+    fn paste(&mut self, after: bool, count: usize, textarea: &mut TextArea) {
+        let yanked = textarea.yank_text();
+        if yanked.is_empty() {
+            return;
+        }
+        let (row, col) = textarea.cursor();
+        if self.linewise {
+            let text = vec![yanked; count].join("\n");
+            if after {
+                textarea.move_cursor(CursorMove::End);
+                textarea.insert_newline();
+                textarea.insert_str(text);
+                jump(textarea, first_non_blank(textarea.lines(), row + 1));
+            } else {
+                textarea.move_cursor(CursorMove::Head);
+                textarea.insert_str(text);
+                textarea.insert_newline();
+                jump(textarea, first_non_blank(textarea.lines(), row));
+            }
+        } else {
+            if after && col < textarea.lines()[row].chars().count() {
+                textarea.move_cursor(CursorMove::Forward);
+            }
+            textarea.insert_str(yanked.repeat(count));
+            textarea.move_cursor(CursorMove::Back);
+        }
+    }
+}
+
+// Normal mode sits on a character rather than after the last one
+fn clamp(textarea: &mut TextArea) {
+    let (row, col) = textarea.cursor();
+    let len = textarea.lines()[row].chars().count();
+    if len > 0 && col >= len {
+        jump(textarea, (row, len - 1));
+    }
+}
+
+fn jump(textarea: &mut TextArea, (row, col): Position) {
+    let fit = |n: usize| n.min(u16::MAX as usize) as u16;
+    textarea.move_cursor(CursorMove::Jump(fit(row), fit(col)));
+}
+
+fn ordered(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+fn first_non_blank(lines: &[String], row: usize) -> Position {
+    let col = lines[row]
+        .chars()
+        .position(|c| !c.is_whitespace())
+        .unwrap_or(0);
+    (row, col)
+}
+
+// `count`th match of `c` on the cursor's line. `f` and `t` look forward, `F` and `T` back,
+// and `t` and `T` stop just short of it
+fn find(textarea: &TextArea, kind: char, c: char, count: usize) -> Option<(Position, Reach)> {
+    let (row, col) = textarea.cursor();
+    let line: Vec<char> = textarea.lines()[row].chars().collect();
+    let found = if kind == 'f' || kind == 't' {
+        (col + 1..line.len())
+            .filter(|i| line[*i] == c)
+            .nth(count - 1)?
+    } else {
+        (0..col).rev().filter(|i| line[*i] == c).nth(count - 1)?
+    };
+    Some(match kind {
+        'f' => ((row, found), Reach::Inclusive),
+        't' => ((row, found - 1), Reach::Inclusive),
+        'F' => ((row, found), Reach::Exclusive),
+        _ => ((row, found + 1), Reach::Exclusive),
+    })
+}
+
+// `r`: overwrite characters from the cursor on, as long as the line has enough of them
+fn replace(textarea: &mut TextArea, c: char, count: usize) {
+    let (row, col) = textarea.cursor();
+    if col.saturating_add(count) > textarea.lines()[row].chars().count() {
+        return;
+    }
+    for _ in 0..count {
+        textarea.delete_next_char();
+    }
+    textarea.insert_str(c.to_string().repeat(count));
+    textarea.move_cursor(CursorMove::Back);
+}
+
+// `J`: pull the next `times` lines up onto the cursor's, one space between each
+fn join(textarea: &mut TextArea, times: usize) {
+    for _ in 0..times {
+        let row = textarea.cursor().0;
+        let lines = textarea.lines();
+        if row + 1 >= lines.len() {
+            break;
+        }
+        let indent = lines[row + 1]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        let needs_space = !lines[row].is_empty() && !lines[row + 1].trim().is_empty();
+        textarea.move_cursor(CursorMove::End);
+        textarea.start_selection();
+        jump(textarea, (row + 1, indent));
+        // With a selection this deletes it, without touching what's been yanked
+        textarea.delete_next_char();
+        if needs_space {
+            textarea.insert_char(' ');
+            textarea.move_cursor(CursorMove::Back);
+        }
+    }
+}
+
+// The day as one run of characters with a '\n' between lines, so motions can cross lines
+struct Text {
+    chars: Vec<char>,
+    // Index of the first character of each line
+    starts: Vec<usize>,
+}
+
+impl Text {
+    fn new(lines: &[String]) -> Self {
+        let mut chars = Vec::new();
+        let mut starts = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            if row > 0 {
+                chars.push('\n');
+            }
+            starts.push(chars.len());
+            chars.extend(line.chars());
+        }
+        Self { chars, starts }
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        let end = self
+            .starts
+            .get(row + 1)
+            .map_or(self.chars.len(), |start| start - 1);
+        end - self.starts[row]
+    }
+
+    fn index(&self, (row, col): Position) -> usize {
+        self.starts[row] + col.min(self.line_len(row))
+    }
+
+    fn position(&self, index: usize) -> Position {
+        let row = self.starts.partition_point(|start| *start <= index) - 1;
+        (row, index - self.starts[row])
+    }
+
+    // Just past the character at `index`, unless it's a line break
+    fn past(&self, index: usize) -> usize {
+        match self.chars.get(index) {
+            Some('\n') | None => index,
+            Some(_) => index + 1,
+        }
+    }
+}
+
+// Words are runs of letters, digits and underscores, or runs of other non-blank
+// characters. Big words (`W`, `B`, `E`) are anything between blanks
+fn class(c: char, big: bool) -> u8 {
+    if c.is_whitespace() {
+        0
+    } else if big || c.is_alphanumeric() || c == '_' {
+        1
+    } else {
+        2
+    }
+}
+
+fn word_forward(text: &Text, index: usize, big: bool) -> usize {
+    let chars = &text.chars;
+    let mut i = index;
+    let Some(start) = chars.get(i).map(|c| class(*c, big)) else {
+        return chars.len();
+    };
+    while start != 0 && i < chars.len() && class(chars[i], big) == start {
+        i += 1;
+    }
+    while i < chars.len() && chars[i].is_whitespace() {
+        // An empty line counts as a word
+        if chars[i] == '\n' && chars.get(i + 1) == Some(&'\n') {
+            return i + 1;
+        }
+        i += 1;
+    }
+    i.min(chars.len())
+}
+
+fn word_back(text: &Text, index: usize, big: bool) -> usize {
+    let chars = &text.chars;
+    if index == 0 {
+        return 0;
+    }
+    let mut i = index - 1;
+    while i > 0 && chars[i].is_whitespace() {
+        i -= 1;
+    }
+    let start = class(chars[i], big);
+    while start != 0 && i > 0 && class(chars[i - 1], big) == start {
+        i -= 1;
+    }
+    i
+}
+
+fn word_end(text: &Text, index: usize, big: bool) -> usize {
+    let chars = &text.chars;
+    if chars.is_empty() {
+        return 0;
+    }
+    let mut i = index + 1;
+    while i < chars.len() && chars[i].is_whitespace() {
+        i += 1;
+    }
+    if i >= chars.len() {
+        return chars.len() - 1;
+    }
+    let start = class(chars[i], big);
+    while i + 1 < chars.len() && class(chars[i + 1], big) == start {
+        i += 1;
+    }
+    i
+}
+
+// The range a text object like `iw`, `a"` or `i(` covers around the cursor
+fn object(text: &Text, cursor: usize, kind: char, inner: bool) -> Option<(usize, usize)> {
+    match kind {
+        'w' | 'W' => word_object(text, cursor, kind == 'W', inner),
+        '"' | '\'' | '`' => quote_object(text, cursor, kind, inner),
+        '(' | ')' | 'b' => pair_object(text, cursor, ('(', ')'), inner),
+        '[' | ']' => pair_object(text, cursor, ('[', ']'), inner),
+        '{' | '}' | 'B' => pair_object(text, cursor, ('{', '}'), inner),
+        '<' | '>' => pair_object(text, cursor, ('<', '>'), inner),
+        _ => None,
+    }
+}
+
+// A word, or the blanks the cursor is on. `aw` takes the blanks after the word too,
+// or the ones before it when it ends the line
+fn word_object(text: &Text, cursor: usize, big: bool, inner: bool) -> Option<(usize, usize)> {
+    let chars = &text.chars;
+    let kind = class(*chars.get(cursor).filter(|c| **c != '\n')?, big);
+    let same = |i: usize| chars[i] != '\n' && class(chars[i], big) == kind;
+    let blank = |i: usize| chars[i] != '\n' && chars[i].is_whitespace();
+    let mut start = cursor;
+    while start > 0 && same(start - 1) {
+        start -= 1;
+    }
+    let mut end = cursor + 1;
+    while end < chars.len() && same(end) {
+        end += 1;
+    }
+    if !inner {
+        let mut after = end;
+        while after < chars.len() && blank(after) {
+            after += 1;
+        }
+        if after > end {
+            end = after;
+        } else {
+            while start > 0 && blank(start - 1) {
+                start -= 1;
+            }
+        }
+    }
+    Some((start, end))
+}
+
+// Quotes pair up from the start of the line. The cursor can be inside a pair, on one
+// of its quotes or anywhere before it on the line
+fn quote_object(text: &Text, cursor: usize, quote: char, inner: bool) -> Option<(usize, usize)> {
+    let (row, col) = text.position(cursor);
+    let start = text.starts[row];
+    let line = &text.chars[start..start + text.line_len(row)];
+    let quotes: Vec<usize> = (0..line.len()).filter(|i| line[*i] == quote).collect();
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|(_, close)| *close >= col)?;
+    if inner {
+        Some((start + open + 1, start + close))
+    } else {
+        Some((start + open, start + close + 1))
+    }
+}
+
+// The nearest brackets around the cursor, which can span lines
+fn pair_object(
+    text: &Text,
+    cursor: usize,
+    (open, close): (char, char),
+    inner: bool,
+) -> Option<(usize, usize)> {
+    let chars = &text.chars;
+    let mut depth = 0;
+    let mut start = None;
+    for i in (0..=cursor.min(chars.len().checked_sub(1)?)).rev() {
+        if chars[i] == close && i != cursor {
+            depth += 1;
+        } else if chars[i] == open {
+            if depth == 0 {
+                start = Some(i);
+                break;
+            }
+            depth -= 1;
+        }
+    }
+    let start = start?;
+    let mut depth = 0;
+    let mut end = None;
+    for (i, c) in chars.iter().enumerate().skip(start + 1) {
+        if *c == open {
+            depth += 1;
+        } else if *c == close {
+            if depth == 0 {
+                end = Some(i);
+                break;
+            }
+            depth -= 1;
+        }
+    }
+    let end = end?;
+    // A block on lines of its own keeps its brackets on theirs
+    if inner && chars.get(start + 1) == Some(&'\n') {
+        Some((start + 2, end))
+    } else if inner {
+        Some((start + 1, end))
+    } else {
+        Some((start, end + 1))
+    }
+}
+
+impl App<'_> {
+    // The modal editor gets the first go at keys in the editor. The ones it doesn't use,
+    // like esc in normal mode and whatever's typed in insert mode, go on to the bindings
+    pub fn vim_input(&mut self, input: Input) -> bool {
+        self.config.vim && self.vim.input(input, &mut self.text_buffer)
+    }
+
+    // Whether keys without a binding should be typed into the editor
+    pub fn inserting(&self) -> bool {
+        !self.config.vim || self.vim.mode == Mode::Insert
+    }
+
+    // "-- NORMAL -- 2d" while editing with vim keys
+    pub fn vim_status(&self) -> Option<String> {
+        if !self.config.vim {
+            return None;
+        }
+        let mut status = format!("-- {} --", self.vim.mode.name());
+        if !self.vim.typed.is_empty() {
+            status.push(' ');
+            status.push_str(&self.vim.typed);
+        }
+        Some(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(lines: &[&str]) -> (Vim, TextArea<'static>) {
+        let lines = lines.iter().map(|line| line.to_string()).collect();
+        (Vim::default(), TextArea::new(lines))
+    }
+
+    fn keys(vim: &mut Vim, textarea: &mut TextArea, keys: &str) {
+        for c in keys.chars() {
+            let input = Input {
+                key: Key::Char(c),
+                ..Input::default()
+            };
+            vim.input(input, textarea);
+        }
+    }
+
+    #[test]
+    fn counts_repeat_and_multiply() {
+        let (mut vim, mut textarea) = editor(&["abcdef"]);
+        keys(&mut vim, &mut textarea, "3x");
+        assert_eq!(textarea.lines(), ["def"]);
+
+        let (mut vim, mut textarea) = editor(&["one two three four five six seven"]);
+        keys(&mut vim, &mut textarea, "2d3w");
+        assert_eq!(textarea.lines(), ["seven"]);
+
+        let (mut vim, mut textarea) = editor(&["a", "b", "c", "d"]);
+        keys(&mut vim, &mut textarea, "j2dd");
+        assert_eq!(textarea.lines(), ["a", "d"]);
+        assert_eq!(vim.typed, "");
+    }
+
+    #[test]
+    fn huge_counts_stop_at_the_end() {
+        let (mut vim, mut textarea) = editor(&["a", "b", "c"]);
+        keys(&mut vim, &mut textarea, "50gg");
+        assert_eq!(textarea.cursor(), (2, 0));
+        keys(&mut vim, &mut textarea, "99999999999999999999999999gg");
+        assert_eq!(textarea.cursor(), (2, 0));
+        keys(&mut vim, &mut textarea, "gg99999999999999999999999999j");
+        assert_eq!(textarea.cursor(), (2, 0));
+        keys(&mut vim, &mut textarea, "gg99999999999999999999999999yy");
+        keys(&mut vim, &mut textarea, "99999999999999999999999999dd");
+        assert_eq!(textarea.lines(), [""]);
+    }
+
+    #[test]
+    fn motions() {
+        let (mut vim, mut textarea) = editor(&["  one two.three", "", "four"]);
+        keys(&mut vim, &mut textarea, "^");
+        assert_eq!(textarea.cursor(), (0, 2));
+        keys(&mut vim, &mut textarea, "w");
+        assert_eq!(textarea.cursor(), (0, 6));
+        keys(&mut vim, &mut textarea, "w");
+        assert_eq!(textarea.cursor(), (0, 9));
+        keys(&mut vim, &mut textarea, "e");
+        assert_eq!(textarea.cursor(), (0, 14));
+        keys(&mut vim, &mut textarea, "B");
+        assert_eq!(textarea.cursor(), (0, 6));
+        keys(&mut vim, &mut textarea, "0ft");
+        assert_eq!(textarea.cursor(), (0, 6));
+        keys(&mut vim, &mut textarea, "02ft");
+        assert_eq!(textarea.cursor(), (0, 10));
+        keys(&mut vim, &mut textarea, "$");
+        assert_eq!(textarea.cursor(), (0, 14));
+        keys(&mut vim, &mut textarea, "}");
+        assert_eq!(textarea.cursor(), (1, 0));
+        keys(&mut vim, &mut textarea, "G");
+        assert_eq!(textarea.cursor(), (2, 0));
+        keys(&mut vim, &mut textarea, "gg");
+        assert_eq!(textarea.cursor(), (0, 2));
+    }
+
+    #[test]
+    fn operators_with_motions() {
+        let (mut vim, mut textarea) = editor(&["one two three"]);
+        keys(&mut vim, &mut textarea, "wcw");
+        assert_eq!(textarea.lines(), ["one  three"]);
+        assert_eq!(textarea.cursor(), (0, 4));
+        assert_eq!(vim.mode, Mode::Insert);
+
+        let (mut vim, mut textarea) = editor(&["say hello, world"]);
+        keys(&mut vim, &mut textarea, "dt,");
+        assert_eq!(textarea.lines(), [", world"]);
+        keys(&mut vim, &mut textarea, "d$");
+        assert_eq!(textarea.lines(), [""]);
+    }
+
+    #[test]
+    fn text_objects() {
+        let (mut vim, mut textarea) = editor(&["say \"hello there\" now"]);
+        keys(&mut vim, &mut textarea, "fhci\"");
+        assert_eq!(textarea.lines(), ["say \"\" now"]);
+        assert_eq!(vim.mode, Mode::Insert);
+
+        let (mut vim, mut textarea) = editor(&["call(a, (b)) done"]);
+        keys(&mut vim, &mut textarea, "f,da(");
+        assert_eq!(textarea.lines(), ["call done"]);
+
+        let (mut vim, mut textarea) = editor(&["one two three"]);
+        keys(&mut vim, &mut textarea, "wdiw");
+        assert_eq!(textarea.lines(), ["one  three"]);
+        keys(&mut vim, &mut textarea, "u0wdaw");
+        assert_eq!(textarea.lines(), ["one three"]);
+
+        // Nothing to act on leaves the text alone
+        let (mut vim, mut textarea) = editor(&["no brackets"]);
+        keys(&mut vim, &mut textarea, "di(");
+        assert_eq!(textarea.lines(), ["no brackets"]);
+        assert_eq!(vim.typed, "");
+    }
+}
@@ -0,0 +1,375 @@
+use tui_textarea::CursorMove;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::app::App;
+use crate::markdown::{heading, numbered_item, FENCE};
+use crate::project::marker_name;
+
+// Width of a line in terminal columns, so wide characters count double and accents not at all
+pub fn columns(text: &str) -> usize {
+    text.width()
+}
+
+// Where a line breaks when it's shown `width` columns wide, as ranges of characters.
+// Rows break after blanks where they can, and a blank at the edge hangs off the end
+// rather than starting the next row
+pub fn wrap_line(line: &str, width: usize) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    if width == 0 {
+        return vec![(0, chars.len())];
+    }
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut used = 0;
+    // Just after the last blank on the row
+    let mut after_blank = None;
+    for (i, c) in chars.iter().enumerate() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width && i > start && !c.is_whitespace() {
+            let end = after_blank.filter(|end| *end > start).unwrap_or(i);
+            rows.push((start, end));
+            start = end;
+            used = chars[start..i].iter().map(|c| c.width().unwrap_or(0)).sum();
+            after_blank = None;
+        }
+        used += w;
+        if c.is_whitespace() {
+            after_blank = Some(i + 1);
+        }
+    }
+    rows.push((start, chars.len()));
+    rows
+}
+
+// Lines reflowing leaves alone: blanks, code blocks, headings, tables and project
+// markers, which all mean something on a line of their own
+fn fixed_lines(lines: &[String]) -> Vec<bool> {
+    let mut in_code = false;
+    lines
+        .iter()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let fence = trimmed.starts_with(FENCE);
+            let fixed = in_code
+                || fence
+                || trimmed.is_empty()
+                || heading(trimmed).is_some()
+                || trimmed.starts_with('|')
+                || marker_name(line).is_some();
+            if fence {
+                in_code = !in_code;
+            }
+            fixed
+        })
+        .collect()
+}
+
+// Lines of text being gathered up to refill, with what goes in front of its first line and the rest
+struct Paragraph<'a> {
+    first: String,
+    rest: String,
+    words: Vec<&'a str>,
+}
+
+impl Paragraph<'_> {
+    fn fill(self, width: usize, filled: &mut Vec<String>) {
+        let mut line = self.first.clone();
+        let mut empty = true;
+        for word in self.words {
+            if !empty && columns(&line) + 1 + columns(word) > width {
+                filled.push(line);
+                line = self.rest.clone();
+                empty = true;
+            }
+            if !empty {
+                line.push(' ');
+            }
+            line.push_str(word);
+            empty = false;
+        }
+        if empty {
+            line.truncate(line.trim_end().len());
+        }
+        filled.push(line);
+    }
+}
+
+// Rewraps lines at `width` columns. Paragraphs are joined up and refilled, list items
+// and quotes keep their markers and line up under them, and everything in `fixed_lines`
+// stays as it is
+pub fn reflow(lines: &[String], width: usize) -> Vec<String> {
+    let mut reflowed = Vec::new();
+    let mut paragraph: Option<Paragraph> = None;
+    for (line, fixed) in lines.iter().zip(fixed_lines(lines)) {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        if fixed {
+            if let Some(paragraph) = paragraph.take() {
+                paragraph.fill(width, &mut reflowed);
+            }
+            reflowed.push(line.clone());
+            continue;
+        }
+        let quote = format!("{}> ", indent);
+        let item = if let Some(text) = trimmed.strip_prefix('>') {
+            // Quotes carry on from one line to the next
+            if let Some(paragraph) = paragraph.as_mut().filter(|p| p.rest == quote) {
+                paragraph.words.extend(text.split_whitespace());
+                continue;
+            }
+            Some((quote.clone(), quote, text))
+        } else if let Some(text) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker))
+        {
+            let marker = format!("{}{}", indent, &trimmed[..2]);
+            Some((marker.clone(), " ".repeat(columns(&marker)), text))
+        } else if let Some((number, text)) = numbered_item(trimmed) {
+            let marker = format!("{}{} ", indent, number);
+            Some((marker.clone(), " ".repeat(columns(&marker)), text))
+        } else {
+            None
+        };
+        if let Some((first, rest, text)) = item {
+            if let Some(paragraph) = paragraph.take() {
+                paragraph.fill(width, &mut reflowed);
+            }
+            paragraph = Some(Paragraph {
+                first,
+                rest,
+                words: text.split_whitespace().collect(),
+            });
+        } else if let Some(paragraph) = paragraph.as_mut() {
+            paragraph.words.extend(trimmed.split_whitespace());
+        } else {
+            paragraph = Some(Paragraph {
+                first: indent.to_string(),
+                rest: indent.to_string(),
+                words: trimmed.split_whitespace().collect(),
+            });
+        }
+    }
+    if let Some(paragraph) = paragraph {
+        paragraph.fill(width, &mut reflowed);
+    }
+    reflowed
+}
+
+// First and last rows of the run of reflowable lines around `row`
+pub fn paragraph_at(lines: &[String], row: usize) -> Option<(usize, usize)> {
+    let fixed = fixed_lines(lines);
+    if *fixed.get(row)? {
+        return None;
+    }
+    let first = (0..row).rev().find(|r| fixed[*r]).map_or(0, |r| r + 1);
+    let last = (row + 1..lines.len())
+        .find(|r| fixed[*r])
+        .map_or(lines.len() - 1, |r| r - 1);
+    Some((first, last))
+}
+
+impl App<'_> {
+    // The configured line length, or the width of the editor when there isn't one
+    fn wrap_width(&self) -> usize {
+        self.config
+            .chars_per_line
+            .or(self.click_areas.editor.map(|rect| rect.width as usize))
+            .unwrap_or(80)
+    }
+
+    // The line and column shown at a row and column of the soft wrapped editor
+    pub fn soft_wrapped_position(&self, row: u16, column: u16, width: u16) -> (u16, u16) {
+        let mut shown = 0;
+        for (line_row, line) in self.text_buffer.lines().iter().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            for (start, end) in wrap_line(line, width as usize) {
+                if shown == row {
+                    let mut used = 0;
+                    let col = (start..end)
+                        .find(|col| {
+                            used += chars[*col].width().unwrap_or(0);
+                            used > column as usize
+                        })
+                        .unwrap_or(end);
+                    return (line_row as u16, col as u16);
+                }
+                shown += 1;
+            }
+        }
+        (u16::MAX, u16::MAX)
+    }
+
+    pub fn reflow_paragraph(&mut self) {
+        let row = self.text_buffer.cursor().0;
+        if let Some((first, last)) = paragraph_at(self.text_buffer.lines(), row) {
+            self.reflow_lines(first, last);
+        }
+    }
+
+    pub fn reflow_day(&mut self) {
+        let last = self.text_buffer.lines().len() - 1;
+        self.reflow_lines(0, last);
+    }
+
+    // Goes through the text area so the reflow can be undone, in two steps as it records
+    // taking the old lines out and putting the new ones in separately
+    fn reflow_lines(&mut self, first: usize, last: usize) {
+        let lines = &self.text_buffer.lines()[first..=last];
+        let reflowed = reflow(lines, self.wrap_width());
+        if reflowed == lines {
+            return;
+        }
+        let (row, col) = self.text_buffer.cursor();
+        self.text_buffer.cancel_selection();
+        self.text_buffer
+            .move_cursor(CursorMove::Jump(first as u16, 0));
+        self.text_buffer.start_selection();
+        self.text_buffer
+            .move_cursor(CursorMove::Jump(last as u16, u16::MAX));
+        self.text_buffer.insert_str(reflowed.join("\n"));
+        // Stay near where the cursor was, as the lines it was on may have gone
+        self.text_buffer
+            .move_cursor(CursorMove::Jump(row as u16, col as u16));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use tui_textarea::TextArea;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn app_with(text: &[&str]) -> App<'static> {
+        let mut app = App::new("wrap-test.postcard".to_string(), Config::default());
+        app.text_buffer = TextArea::new(lines(text));
+        app
+    }
+
+    #[test]
+    fn measures_terminal_columns() {
+        assert_eq!(columns("abc"), 3);
+        assert_eq!(columns("日本"), 4);
+        assert_eq!(columns("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn wraps_after_blanks_and_hangs_them_off_the_edge() {
+        assert_eq!(wrap_line("hello world", 5), [(0, 6), (6, 11)]);
+        assert_eq!(wrap_line("abc def", 3), [(0, 4), (4, 7)]);
+        assert_eq!(wrap_line("", 5), [(0, 0)]);
+        assert_eq!(wrap_line("no width", 0), [(0, 8)]);
+    }
+
+    #[test]
+    fn wraps_wide_characters_by_their_columns() {
+        assert_eq!(
+            wrap_line("日本語テキスト", 5),
+            [(0, 2), (2, 4), (4, 6), (6, 7)]
+        );
+        assert_eq!(wrap_line("ab😀cd", 3), [(0, 2), (2, 4), (4, 5)]);
+    }
+
+    #[test]
+    fn breaks_overlong_words() {
+        assert_eq!(wrap_line("abcdefgh", 3), [(0, 3), (3, 6), (6, 8)]);
+        // Reflowing never splits a word, it just lets it run over
+        assert_eq!(
+            reflow(&lines(&["supercalifragilistic word"]), 5),
+            ["supercalifragilistic", "word"]
+        );
+    }
+
+    #[test]
+    fn refills_paragraphs() {
+        assert_eq!(
+            reflow(&lines(&["one two three", "four five"]), 10),
+            ["one two", "three four", "five"]
+        );
+        assert_eq!(
+            reflow(&lines(&["  indented text"]), 10),
+            ["  indented", "  text"]
+        );
+        assert_eq!(reflow(&lines(&["日本 語 テキ"]), 7), ["日本 語", "テキ"]);
+    }
+
+    #[test]
+    fn keeps_list_and_quote_markers() {
+        assert_eq!(
+            reflow(&lines(&["- alpha beta gamma"]), 12),
+            ["- alpha beta", "  gamma"]
+        );
+        assert_eq!(
+            reflow(&lines(&["12. alpha beta gamma"]), 14),
+            ["12. alpha beta", "    gamma"]
+        );
+        assert_eq!(
+            reflow(&lines(&["> one two", "> three four"]), 9),
+            ["> one two", "> three", "> four"]
+        );
+        // Each item is its own paragraph
+        assert_eq!(reflow(&lines(&["- a", "- b"]), 80), ["- a", "- b"]);
+    }
+
+    #[test]
+    fn leaves_fixed_lines_alone() {
+        let text = lines(&["a b", "```", "long code line here", "```", "c", "d"]);
+        assert_eq!(
+            reflow(&text, 3),
+            ["a b", "```", "long code line here", "```", "c d"]
+        );
+        let text = lines(&["# A long heading", "x", "", "| a | b |", "y"]);
+        assert_eq!(reflow(&text, 5), text);
+    }
+
+    #[test]
+    fn finds_the_paragraph_around_a_row() {
+        let text = lines(&["# H", "a", "b", "", "c"]);
+        assert_eq!(paragraph_at(&text, 1), Some((1, 2)));
+        assert_eq!(paragraph_at(&text, 2), Some((1, 2)));
+        assert_eq!(paragraph_at(&text, 4), Some((4, 4)));
+        assert_eq!(paragraph_at(&text, 0), None);
+        assert_eq!(paragraph_at(&text, 9), None);
+    }
+
+    #[test]
+    fn maps_soft_wrapped_rows_back_to_lines() {
+        let app = app_with(&["hello world", "日本語"]);
+        assert_eq!(app.soft_wrapped_position(0, 1, 5), (0, 1));
+        assert_eq!(app.soft_wrapped_position(1, 2, 5), (0, 8));
+        // Past the end of a row is its end
+        assert_eq!(app.soft_wrapped_position(0, 9, 5), (0, 6));
+        // Either half of a wide character is that character
+        assert_eq!(app.soft_wrapped_position(2, 1, 5), (1, 0));
+        assert_eq!(app.soft_wrapped_position(2, 3, 5), (1, 1));
+        assert_eq!(app.soft_wrapped_position(9, 0, 5), (u16::MAX, u16::MAX));
+    }
+
+    #[test]
+    fn reflows_in_the_text_area_and_can_be_undone() {
+        let mut app = app_with(&["one two three", "four five", "", "six seven"]);
+        app.config.chars_per_line = Some(10);
+        app.reflow_paragraph();
+        assert_eq!(
+            app.text_buffer.lines(),
+            ["one two", "three four", "five", "", "six seven"]
+        );
+
+        app.text_buffer.undo();
+        app.text_buffer.undo();
+        assert_eq!(
+            app.text_buffer.lines(),
+            ["one two three", "four five", "", "six seven"]
+        );
+
+        app.config.chars_per_line = Some(3);
+        app.reflow_day();
+        assert_eq!(
+            app.text_buffer.lines(),
+            ["one", "two", "three", "four", "five", "", "six", "seven"]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use englog::app::{App, CurrentScreen, Popup};
+use englog::calendar::Activity;
+use englog::config::Config;
+use englog::keymap::{Action, Context};
+use englog::tui::{Event, Tui};
+use englog::update::update;
+use ratatui::{backend::TestBackend, Terminal};
+
+fn type_keys(app: &mut App, tui: &Tui<TestBackend>, keys: &str) {
+    for c in keys.chars() {
+        let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        update(Event::Key(key), app, tui);
+    }
+}
+
+#[tokio::test]
+async fn keys_from_an_unfinished_sequence_are_typed() {
+    let config = Config {
+        keys: toml::from_str("[filter]\nclear-filter = [\"x y z\", \"esc\"]\n").unwrap(),
+        ..Config::default()
+    };
+    let mut app = App::new("keys-test.postcard".to_string(), config);
+    let tui = Tui::new(Terminal::new(TestBackend::new(80, 20)).unwrap());
+    app.current_screen = CurrentScreen::Main(true);
+
+    type_keys(&mut app, &tui, "xya");
+    assert_eq!(app.filter.as_deref(), Some("xya"));
+    assert!(app.pending_keys.is_empty());
+
+    // The key that broke the sequence can start one of its own
+    type_keys(&mut app, &tui, "xxy");
+    assert_eq!(app.filter.as_deref(), Some("xyax"));
+    assert_eq!(app.pending_keys.len(), 2);
+    type_keys(&mut app, &tui, "z");
+    assert_eq!(app.filter, None);
+}
+
+#[tokio::test]
+async fn confirm_popups_show_the_bound_key() {
+    let config = Config {
+        keys: toml::from_str("[popup]\nconfirm = [\"o\"]\n").unwrap(),
+        ..Config::default()
+    };
+    let mut app = App::new("keys-test.postcard".to_string(), config);
+    app.popup = Some(Popup::ConfDeleteDay);
+    let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    terminal.draw(|f| englog::ui::ui(f, &mut app)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let text: String = (0..buffer.area.height)
+        .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+        .map(|(x, y)| buffer.get(x, y).symbol())
+        .collect();
+    assert!(text.contains("o for yes"));
+    assert!(!text.contains("y for yes"));
+}
+
+#[tokio::test]
+async fn palette_runs_commands_from_other_screens() {
+    let mut app = App::new("keys-test.postcard".to_string(), Config::default());
+    let tui = Tui::new(Terminal::new(TestBackend::new(80, 20)).unwrap());
+
+    let f1 = KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE);
+    update(Event::Key(f1), &mut app, &tui);
+    type_keys(&mut app, &tui, "toggle-activity");
+    let command = &app.palette_commands()[0];
+    assert_eq!(command.context, Context::Heatmap);
+    assert_eq!(command.action, Action::ToggleActivity);
+
+    let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+    update(Event::Key(enter), &mut app, &tui);
+    assert!(app.popup.is_none());
+    assert!(app.current_screen == CurrentScreen::Heatmap);
+    assert!(app.heatmap_activity == Activity::Entries);
+}
@@ -0,0 +1,156 @@
+use chrono::{Days, NaiveDate};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use englog::app::{App, CurrentScreen, Day, Popup};
+use englog::config::Config;
+use englog::tui::{Event, Tui};
+use englog::update::update;
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+
+fn app_with_days(count: u64) -> App<'static> {
+    let mut app = App::new("resize-test.postcard".to_string(), Config::default());
+    let start = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+    for offset in 0..count {
+        let mut day = Day::new(start.checked_add_days(Days::new(offset)).unwrap());
+        day.content = vec![format!("notes for day {}", offset)];
+        app.add_day(day);
+    }
+    app.set_filter(None);
+    app
+}
+
+fn screen_text(terminal: &Terminal<TestBackend>) -> String {
+    backend_text(terminal.backend())
+}
+
+fn backend_text(backend: &TestBackend) -> String {
+    let buffer = backend.buffer();
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            text.push_str(buffer.get(x, y).symbol());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn resize(terminal: &mut Terminal<TestBackend>, width: u16, height: u16) {
+    terminal.backend_mut().resize(width, height);
+    terminal.resize(Rect::new(0, 0, width, height)).unwrap();
+}
+
+fn draw(terminal: &mut Terminal<TestBackend>, app: &mut App) {
+    terminal.draw(|f| englog::ui::ui(f, app)).unwrap();
+}
+
+fn selected_date(app: &App) -> String {
+    app.selected_day().unwrap().date_pretty()
+}
+
+#[test]
+fn selection_stays_visible_across_resizes() {
+    let mut app = app_with_days(200);
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    draw(&mut terminal, &mut app);
+
+    for _ in 0..60 {
+        app.increment_selected();
+    }
+    draw(&mut terminal, &mut app);
+    assert!(screen_text(&terminal).contains(&selected_date(&app)));
+
+    for (width, height) in [(60, 12), (40, 9), (150, 50), (80, 10)] {
+        resize(&mut terminal, width, height);
+        draw(&mut terminal, &mut app);
+        assert!(
+            screen_text(&terminal).contains(&selected_date(&app)),
+            "selected day hidden at {}x{}",
+            width,
+            height
+        );
+    }
+
+    // Jumping a page at a time used to leave the selection below the bottom of the list
+    for _ in 0..10 {
+        app.currently_selected = (app.currently_selected + 10).min(app.filtered.len() - 1);
+        draw(&mut terminal, &mut app);
+        assert!(screen_text(&terminal).contains(&selected_date(&app)));
+    }
+}
+
+#[test]
+fn every_screen_survives_a_resize() {
+    let mut app = app_with_days(30);
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    let screens = [
+        CurrentScreen::Main(false),
+        CurrentScreen::Main(true),
+        CurrentScreen::ViewingDay,
+        CurrentScreen::Projects,
+        CurrentScreen::People,
+        CurrentScreen::SearchResults,
+        CurrentScreen::Calendar,
+        CurrentScreen::Heatmap,
+        CurrentScreen::Stats,
+    ];
+    // So the stats screen has something to draw
+    app.show_stats();
+    for screen in screens {
+        app.current_screen = screen;
+        app.load_text();
+        draw(&mut terminal, &mut app);
+        for (width, height) in [(20, 6), (200, 60), (100, 30)] {
+            resize(&mut terminal, width, height);
+            draw(&mut terminal, &mut app);
+        }
+    }
+
+    app.current_screen = CurrentScreen::Main(false);
+    app.popup = Some(Popup::Info(englog::app::Info::About));
+    resize(&mut terminal, 30, 8);
+    draw(&mut terminal, &mut app);
+}
+
+#[tokio::test]
+async fn resize_events_redraw_at_the_new_size() {
+    let mut app = app_with_days(100);
+    let mut tui = Tui::new(Terminal::new(TestBackend::new(120, 40)).unwrap());
+    for _ in 0..80 {
+        app.increment_selected();
+    }
+    tui.draw(&mut app).unwrap();
+
+    for (width, height) in [(60, 12), (40, 9), (150, 50)] {
+        tui.backend_mut().resize(width, height);
+        tui.event_tx.send(Event::Resize(width, height)).unwrap();
+        let event = tui.next().await.unwrap();
+        assert!(matches!(event, Event::Resize(..)));
+        update(event, &mut app, &tui);
+        tui.draw(&mut app).unwrap();
+        assert!(
+            backend_text(tui.backend()).contains(&selected_date(&app)),
+            "selected day hidden at {}x{}",
+            width,
+            height
+        );
+    }
+}
+
+#[tokio::test]
+async fn paging_an_empty_filter_keeps_the_selection_at_the_top() {
+    let mut app = app_with_days(20);
+    let mut tui = Tui::new(Terminal::new(TestBackend::new(80, 20)).unwrap());
+    for _ in 0..5 {
+        app.increment_selected();
+    }
+    app.set_filter(Some("nothing matches this".to_string()));
+    assert!(app.filtered.is_empty());
+
+    let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+    update(Event::Key(ctrl_d), &mut app, &tui);
+    assert_eq!(app.currently_selected, 0);
+    tui.draw(&mut app).unwrap();
+
+    app.current_screen = CurrentScreen::ViewingDay;
+    tui.draw(&mut app).unwrap();
+}